@@ -12,6 +12,7 @@ use rust_engine_3d::resource::resource::CallbackLoadRenderPassCreateInfo;
 use rust_engine_3d::utilities::system::{ptr_as_mut, ptr_as_ref};
 use winit::event::VirtualKeyCode;
 
+use crate::application::headless;
 use crate::game_module::character::character_manager::CharacterManager;
 use crate::game_module::game_client::GameClient;
 use crate::game_module::game_constants;
@@ -51,7 +52,7 @@ impl ApplicationBase for Application {
         let application = ptr_as_ref(self);
         self.get_game_resources_mut().initialize_game_resources(engine_core.get_engine_resources());
         self.get_game_resources_mut().load_game_resources(engine_core.get_renderer_context());
-        self.get_character_manager_mut().initialize_character_manager(application);
+        self.get_character_manager_mut().initialize_character_manager(application.get_game_scene_manager(), application.get_game_resources());
         self.get_game_scene_manager_mut().initialize_game_scene_manager(application, engine_core, window_size);
         self.get_game_ui_manager_mut().initialize_game_ui_manager(engine_core, application);
         self.get_game_controller_mut().initialize_game_controller(application);
@@ -206,13 +207,13 @@ impl ApplicationBase for Application {
         let font_manager = engine_core.get_font_manager_mut();
         font_manager.clear_logs();
 
-        // update managers
+        // update managers, cutscenes also play outside of the game mode since the simulation always runs
+        self._game_client.update_cutscene(delta_time);
         if self._is_game_mode {
             self._game_client.update_game_mode(delta_time);
             self.get_game_ui_manager_mut().set_crosshair_pos(&engine_core._mouse_move_data._mouse_pos);
         }
-        self._game_scene_manager.update_game_scene_manager(engine_core, delta_time);
-        self._character_manager.update_character_manager(engine_core, delta_time);
+        self._game_scene_manager.update_game_scene_manager(delta_time);
         self._game_ui_manager.as_mut().update_game_ui(delta_time);
     }
 }
@@ -274,6 +275,16 @@ impl Application {
 }
 
 pub fn run_application() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.iter().any(|arg| arg == headless::HEADLESS_BENCHMARK_ARG) {
+        headless::run_headless_benchmark(&args);
+        return;
+    }
+    if args.iter().any(|arg| arg == headless::HEADLESS_ARG) {
+        headless::run_headless_application(&args);
+        return;
+    }
+
     // application setting
     let app_name: String = "Stone Age".to_string();
     let app_version: u32 = 1;
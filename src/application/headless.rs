@@ -0,0 +1,307 @@
+use std::time::Instant;
+
+use nalgebra::Vector3;
+use rust_engine_3d::utilities::system::{ptr_as_mut, ptr_as_ref};
+
+use crate::game_module::character::block::BlockCreateInfo;
+use crate::game_module::character::character::CharacterCreateInfo;
+use crate::game_module::character::character_manager::CharacterManager;
+use crate::game_module::game_resource::GameResources;
+use crate::game_module::game_scene_manager::GameSceneManager;
+use crate::game_module::headless_scene_backend::HeadlessSceneBackend;
+
+pub const HEADLESS_ARG: &str = "--headless";
+pub const HEADLESS_DEFAULT_GAME_SCENE: &str = "intro_stage";
+pub const HEADLESS_DEFAULT_FRAME_COUNT: u64 = 600;
+pub const HEADLESS_DELTA_TIME: f64 = 1.0 / 60.0;
+pub const HEADLESS_TICK_RATE_ARG: &str = "--tick-rate";
+pub const HEADLESS_BENCHMARK_ARG: &str = "--headless-benchmark";
+pub const HEADLESS_BENCHMARK_BLOCK_COUNT: u64 = 4000;
+pub const HEADLESS_BENCHMARK_NPC_COUNT: u64 = 300;
+pub const HEADLESS_BENCHMARK_ROW_LENGTH: u64 = 500;
+
+// Runs the gameplay simulation without a window, a vulkan device or an audio device.
+pub struct HeadlessApplication {
+    pub _scene_backend: *const HeadlessSceneBackend,
+    pub _game_resources: Box<GameResources>,
+    pub _game_scene_manager: Box<GameSceneManager>,
+    pub _character_manager: Box<CharacterManager>,
+    pub _frame_index: u64,
+}
+
+impl HeadlessApplication {
+    pub fn create_headless_application() -> Box<HeadlessApplication> {
+        Box::new(HeadlessApplication {
+            _scene_backend: std::ptr::null(),
+            _game_resources: GameResources::create_game_resources(),
+            _game_scene_manager: GameSceneManager::create_game_scene_manager(),
+            _character_manager: CharacterManager::create_character_manager(),
+            _frame_index: 0,
+        })
+    }
+
+    pub fn initialize_headless_application(&mut self) {
+        let scene_backend = Box::new(HeadlessSceneBackend::create_headless_scene_backend());
+        self._scene_backend = scene_backend.as_ref();
+        self._game_resources.load_game_resources_headless();
+        self._game_scene_manager.initialize_game_scene_manager_headless(
+            self._character_manager.as_ref(),
+            self._game_resources.as_ref(),
+            scene_backend,
+        );
+        self._character_manager.initialize_character_manager(
+            self._game_scene_manager.as_ref(),
+            self._game_resources.as_ref(),
+        );
+    }
+
+    pub fn terminate_headless_application(&mut self) {
+        self._game_scene_manager.close_game_scene_data();
+        self._game_resources.destroy_game_resources();
+    }
+
+    pub fn get_scene_backend(&self) -> &HeadlessSceneBackend {
+        ptr_as_ref(self._scene_backend)
+    }
+    pub fn get_scene_backend_mut(&self) -> &mut HeadlessSceneBackend {
+        ptr_as_mut(self._scene_backend)
+    }
+    pub fn get_game_scene_manager(&self) -> &GameSceneManager {
+        self._game_scene_manager.as_ref()
+    }
+    pub fn get_game_scene_manager_mut(&mut self) -> &mut GameSceneManager {
+        self._game_scene_manager.as_mut()
+    }
+    pub fn get_character_manager(&self) -> &CharacterManager {
+        self._character_manager.as_ref()
+    }
+    pub fn get_character_manager_mut(&mut self) -> &mut CharacterManager {
+        self._character_manager.as_mut()
+    }
+
+    pub fn open_game_scene(&mut self, game_scene_data_name: &str) {
+        self._game_scene_manager.open_game_scene_data(game_scene_data_name);
+    }
+
+    // overrides the tick rate of the scene data, returns false for a rate that isn't a positive number
+    pub fn set_tick_rate(&mut self, tick_rate: f64) -> bool {
+        self._game_scene_manager._fixed_time_step.set_tick_rate(tick_rate)
+    }
+
+    // a flat block floor with a platform over every fourth block and npcs spread over it
+    pub fn open_benchmark_scene(&mut self, block_count: u64, npc_count: u64) {
+        let block_size = 2.0;
+        for i in 0..block_count {
+            let row = i / HEADLESS_BENCHMARK_ROW_LENGTH;
+            let column = i % HEADLESS_BENCHMARK_ROW_LENGTH;
+            let height = if 0 == column % 4 && 0 != row % 2 { 12.0 } else { 8.0 };
+            let block_create_info = BlockCreateInfo {
+                _block_data_name: String::from("cliff_grass"),
+                _position: Vector3::new(column as f32 * block_size, height, (row / 2) as f32 * block_size),
+                _rotation: Vector3::zeros(),
+                _scale: Vector3::new(1.0, 1.0, 1.0),
+            };
+            self._game_scene_manager.create_block(&format!("block{}", i), &block_create_info);
+        }
+
+        let floor_length = block_count.min(HEADLESS_BENCHMARK_ROW_LENGTH) as f32 * block_size;
+        let player_create_info = CharacterCreateInfo {
+            _character_data_name: String::from("jack"),
+            _position: Vector3::new(floor_length * 0.5, 10.0, 0.0),
+            _rotation: Vector3::zeros(),
+            _scale: Vector3::new(0.25, 0.25, 0.25),
+        };
+        self._character_manager.create_character("player", &player_create_info, true);
+        for i in 0..npc_count {
+            let npc_create_info = CharacterCreateInfo {
+                _character_data_name: String::from("mutant"),
+                _position: Vector3::new(floor_length * (i as f32 + 0.5) / npc_count as f32, 10.0, 0.0),
+                _rotation: Vector3::zeros(),
+                _scale: Vector3::new(1.25, 1.25, 1.25),
+            };
+            self._character_manager.create_character(&format!("npc{}", i), &npc_create_info, false);
+        }
+    }
+
+    pub fn update_headless_application(&mut self, delta_time: f64) {
+        self._game_scene_manager.update_game_scene_manager(delta_time);
+        self._frame_index += 1;
+    }
+
+    pub fn print_summary(&self) {
+        let statistics = &self.get_scene_backend()._statistics;
+        println!("headless simulation: {} frames, {} ticks", self._frame_index, self.get_game_scene_manager()._fixed_time_step._tick_count);
+        println!("    characters: {}", self._character_manager._characters.len());
+        if let Some(player) = self._character_manager._player.as_ref() {
+            println!("    player position: {:?}", player.borrow().get_position());
+        }
+        for (audio_bank_name, count) in statistics._audio_bank_counts.iter() {
+            println!("    audio {}: {}", audio_bank_name, count);
+        }
+        for (effect_data_name, count) in statistics._effect_counts.iter() {
+            println!("    effect {}: {}", effect_data_name, count);
+        }
+    }
+}
+
+// value of a "--name value" option
+pub fn get_option_value<'a>(args: &'a [String], option_name: &str) -> Option<&'a String> {
+    args.iter().skip_while(|arg| arg.as_str() != option_name).nth(1)
+}
+
+// applies the --tick-rate option, reports an invalid value and returns false
+pub fn apply_tick_rate_arg(application: &mut HeadlessApplication, args: &[String]) -> bool {
+    let tick_rate = match get_option_value(args, HEADLESS_TICK_RATE_ARG) {
+        Some(tick_rate) => tick_rate,
+        None => return true,
+    };
+    let is_valid = match tick_rate.parse::<f64>() {
+        Ok(tick_rate) => application.set_tick_rate(tick_rate),
+        Err(_) => false,
+    };
+    if false == is_valid {
+        eprintln!("{} must be a positive number: {:?}", HEADLESS_TICK_RATE_ARG, tick_rate);
+    }
+    is_valid
+}
+
+// arguments following arg_name up to the first option
+pub fn get_positional_args<'a>(args: &'a [String], arg_name: &'a str) -> impl Iterator<Item = &'a String> {
+    args.iter().skip_while(move |arg| arg.as_str() != arg_name).skip(1).take_while(|arg| false == arg.starts_with("--"))
+}
+
+// usage: --headless [game_scene_name] [frame_count] [--tick-rate ticks_per_second]
+pub fn run_headless_application(args: &[String]) {
+    let mut positional_args = get_positional_args(args, HEADLESS_ARG);
+    let game_scene_data_name = match positional_args.next() {
+        Some(game_scene_data_name) => game_scene_data_name.clone(),
+        None => String::from(HEADLESS_DEFAULT_GAME_SCENE),
+    };
+    let frame_count: u64 = match positional_args.next() {
+        Some(frame_count) => frame_count.parse().expect("frame_count must be a number"),
+        None => HEADLESS_DEFAULT_FRAME_COUNT,
+    };
+
+    let mut application = HeadlessApplication::create_headless_application();
+    application.initialize_headless_application();
+    application.open_game_scene(&game_scene_data_name);
+    if false == apply_tick_rate_arg(&mut application, args) {
+        application.terminate_headless_application();
+        std::process::exit(1);
+    }
+    for _ in 0..frame_count {
+        application.update_headless_application(HEADLESS_DELTA_TIME);
+    }
+    application.print_summary();
+    application.terminate_headless_application();
+}
+
+// usage: --headless-benchmark [block_count] [npc_count] [frame_count] [--tick-rate ticks_per_second]
+pub fn run_headless_benchmark(args: &[String]) {
+    let mut positional_args = get_positional_args(args, HEADLESS_BENCHMARK_ARG);
+    let block_count: u64 = match positional_args.next() {
+        Some(block_count) => block_count.parse().expect("block_count must be a number"),
+        None => HEADLESS_BENCHMARK_BLOCK_COUNT,
+    };
+    let npc_count: u64 = match positional_args.next() {
+        Some(npc_count) => npc_count.parse().expect("npc_count must be a number"),
+        None => HEADLESS_BENCHMARK_NPC_COUNT,
+    };
+    let frame_count: u64 = match positional_args.next() {
+        Some(frame_count) => frame_count.parse().expect("frame_count must be a number"),
+        None => HEADLESS_DEFAULT_FRAME_COUNT,
+    };
+
+    let mut application = HeadlessApplication::create_headless_application();
+    application.initialize_headless_application();
+    application.open_benchmark_scene(block_count, npc_count);
+    if false == apply_tick_rate_arg(&mut application, args) {
+        application.terminate_headless_application();
+        std::process::exit(1);
+    }
+    let start_time = Instant::now();
+    for _ in 0..frame_count {
+        application.update_headless_application(HEADLESS_DELTA_TIME);
+    }
+    let elapsed_time = start_time.elapsed().as_secs_f64();
+    println!("headless benchmark: {} blocks, {} npcs", block_count, npc_count);
+    println!("    total: {:.3} s, frame: {:.3} ms", elapsed_time, elapsed_time * 1000.0 / frame_count.max(1) as f64);
+    application.print_summary();
+    application.terminate_headless_application();
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Vector3;
+    use crate::game_module::scene_backend::RenderObjectProxy;
+    use super::*;
+
+    fn is_overlapped(min: &Vector3<f32>, max: &Vector3<f32>, other_min: &Vector3<f32>, other_max: &Vector3<f32>) -> bool {
+        (0..3).all(|i| other_min[i] <= max[i] && min[i] <= other_max[i])
+    }
+
+    fn get_addresses(colliders: &[*const dyn RenderObjectProxy]) -> Vec<usize> {
+        let mut addresses: Vec<usize> = colliders.iter().map(|collider| *collider as *const () as usize).collect();
+        addresses.sort();
+        addresses
+    }
+
+    #[test]
+    fn test_benchmark_broadphase_matches_linear_scan() {
+        let mut application = HeadlessApplication::create_headless_application();
+        application.initialize_headless_application();
+        application.open_benchmark_scene(HEADLESS_BENCHMARK_BLOCK_COUNT, HEADLESS_BENCHMARK_NPC_COUNT);
+        for _ in 0..30 {
+            application.update_headless_application(HEADLESS_DELTA_TIME);
+        }
+
+        let game_scene_manager = application.get_game_scene_manager();
+        let character_manager = application.get_character_manager();
+        assert_eq!(HEADLESS_BENCHMARK_BLOCK_COUNT as usize, game_scene_manager._collider_spatial_hash.len());
+        let delta_time = game_scene_manager._fixed_time_step.get_tick_delta_time() as f32;
+        for character in character_manager._characters.values() {
+            let character = character.borrow();
+            let (region_min, region_max) = character.get_collision_query_region(delta_time);
+
+            // colliders
+            let colliders = game_scene_manager.query_colliders_in_region(&region_min, &region_max);
+            let expected_colliders: Vec<*const dyn RenderObjectProxy> = game_scene_manager._blocks.values().filter_map(|block| {
+                let render_object = block.borrow()._render_object.as_ptr() as *const dyn RenderObjectProxy;
+                let bound_box = ptr_as_ref(render_object).get_bound_box();
+                if is_overlapped(&region_min, &region_max, &bound_box._min, &bound_box._max) { Some(render_object) } else { None }
+            }).collect();
+            assert_eq!(get_addresses(&expected_colliders), get_addresses(&colliders));
+
+            // characters
+            let mut character_ids: Vec<u64> = character_manager.query_characters_in_region(&region_min, &region_max).iter().map(|other| other.borrow().get_character_id()).collect();
+            character_ids.sort();
+            let mut expected_character_ids: Vec<u64> = character_manager._characters.iter().filter_map(|(character_id, other)| {
+                let (other_min, other_max) = if *character_id == character.get_character_id() {
+                    character.get_collision_bound_box()
+                } else {
+                    other.borrow().get_collision_bound_box()
+                };
+                if is_overlapped(&region_min, &region_max, &other_min, &other_max) { Some(*character_id) } else { None }
+            }).collect();
+            expected_character_ids.sort();
+            assert_eq!(expected_character_ids, character_ids);
+        }
+        application.terminate_headless_application();
+    }
+
+    #[test]
+    fn test_tick_rate_arg() {
+        let mut application = HeadlessApplication::create_headless_application();
+        let args: Vec<String> = ["--headless", "intro_stage", "--tick-rate"].iter().map(|arg| String::from(*arg)).collect();
+        for tick_rate in ["abc", "-5", "0"] {
+            let mut args = args.clone();
+            args.push(String::from(tick_rate));
+            assert!(false == apply_tick_rate_arg(&mut application, &args));
+        }
+        assert!(apply_tick_rate_arg(&mut application, &args[..2]));
+        let mut args = args.clone();
+        args.push(String::from("30"));
+        assert!(apply_tick_rate_arg(&mut application, &args));
+        assert_eq!(30.0, application.get_game_scene_manager()._fixed_time_step._tick_rate);
+    }
+}
@@ -0,0 +1,327 @@
+use nalgebra::Vector3;
+use serde::{Deserialize, Serialize};
+
+use crate::game_module::game_constants::*;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct CameraRigData {
+    // offset from the follow target to the point the camera looks at
+    pub _target_offset: Vector3<f32>,
+    // half extents of the box the target moves in without moving the camera
+    pub _dead_zone: Vector3<f32>,
+    // approximate time to reach the goal, 0.0 follows immediately
+    pub _smooth_time: f32,
+    // the camera leads the target by its velocity times this
+    pub _look_ahead_time: f32,
+    pub _max_look_ahead: f32,
+    pub _look_ahead_smooth_time: f32,
+    // level bounds the focus point is clamped to
+    pub _bounds_min: Option<Vector3<f32>>,
+    pub _bounds_max: Option<Vector3<f32>>,
+    pub _shake: CameraShakeData,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct CameraShakeData {
+    // offset and pitch at full trauma, the shake scales with trauma squared
+    pub _max_offset: Vector3<f32>,
+    pub _max_pitch: f32,
+    pub _frequency: f32,
+    // trauma lost per second
+    pub _trauma_decay: f32,
+}
+
+// trauma based shake, damage events add trauma and it decays over time
+pub struct CameraShake {
+    pub _trauma: f32,
+    pub _time: f32,
+    pub _offset: Vector3<f32>,
+    pub _pitch: f32,
+}
+
+pub struct CameraRig {
+    pub _is_initialized: bool,
+    pub _goal_position: Vector3<f32>,
+    pub _focus_position: Vector3<f32>,
+    pub _focus_velocity: Vector3<f32>,
+    pub _look_ahead: Vector3<f32>,
+    pub _look_ahead_velocity: Vector3<f32>,
+    pub _camera_shake: CameraShake,
+}
+
+impl Default for CameraRigData {
+    fn default() -> CameraRigData {
+        CameraRigData {
+            _target_offset: Vector3::new(0.0, CAMERA_OFFSET_Y, 0.0),
+            _dead_zone: Vector3::new(CAMERA_DEAD_ZONE_X, CAMERA_DEAD_ZONE_Y, CAMERA_DEAD_ZONE_X),
+            _smooth_time: CAMERA_SMOOTH_TIME,
+            _look_ahead_time: CAMERA_LOOK_AHEAD_TIME,
+            _max_look_ahead: CAMERA_MAX_LOOK_AHEAD,
+            _look_ahead_smooth_time: CAMERA_LOOK_AHEAD_SMOOTH_TIME,
+            _bounds_min: None,
+            _bounds_max: None,
+            _shake: CameraShakeData::default(),
+        }
+    }
+}
+
+impl Default for CameraShakeData {
+    fn default() -> CameraShakeData {
+        CameraShakeData {
+            _max_offset: Vector3::new(CAMERA_SHAKE_MAX_OFFSET, CAMERA_SHAKE_MAX_OFFSET, 0.0),
+            _max_pitch: CAMERA_SHAKE_MAX_PITCH,
+            _frequency: CAMERA_SHAKE_FREQUENCY,
+            _trauma_decay: CAMERA_SHAKE_TRAUMA_DECAY,
+        }
+    }
+}
+
+// critically damped spring toward target, velocity carries over between calls
+pub fn smooth_damp(current: f32, target: f32, velocity: &mut f32, smooth_time: f32, delta_time: f32) -> f32 {
+    if smooth_time <= 0.0 {
+        *velocity = 0.0;
+        return target;
+    }
+
+    let omega = 2.0 / smooth_time;
+    let x = omega * delta_time;
+    let exp = 1.0 / (1.0 + x + 0.48 * x * x + 0.235 * x * x * x);
+    let change = current - target;
+    let temp = (*velocity + omega * change) * delta_time;
+    *velocity = (*velocity - omega * temp) * exp;
+    target + (change + temp) * exp
+}
+
+pub fn smooth_damp_vector(current: &Vector3<f32>, target: &Vector3<f32>, velocity: &mut Vector3<f32>, smooth_time: f32, delta_time: f32) -> Vector3<f32> {
+    Vector3::new(
+        smooth_damp(current.x, target.x, &mut velocity.x, smooth_time, delta_time),
+        smooth_damp(current.y, target.y, &mut velocity.y, smooth_time, delta_time),
+        smooth_damp(current.z, target.z, &mut velocity.z, smooth_time, delta_time),
+    )
+}
+
+// moves goal_position just enough to keep target_position inside the dead zone around it
+pub fn apply_dead_zone(goal_position: &Vector3<f32>, target_position: &Vector3<f32>, dead_zone: &Vector3<f32>) -> Vector3<f32> {
+    let mut goal_position = goal_position.clone_owned();
+    for i in 0..3 {
+        let offset = target_position[i] - goal_position[i];
+        if dead_zone[i] < offset {
+            goal_position[i] = target_position[i] - dead_zone[i];
+        } else if offset < -dead_zone[i] {
+            goal_position[i] = target_position[i] + dead_zone[i];
+        }
+    }
+    goal_position
+}
+
+pub fn get_look_ahead(velocity: &Vector3<f32>, look_ahead_time: f32, max_look_ahead: f32) -> Vector3<f32> {
+    let look_ahead = Vector3::new(velocity.x, 0.0, velocity.z) * look_ahead_time;
+    let distance = look_ahead.norm();
+    if max_look_ahead < distance {
+        look_ahead * (max_look_ahead / distance)
+    } else {
+        look_ahead
+    }
+}
+
+pub fn clamp_to_bounds(position: &Vector3<f32>, bounds_min: Option<&Vector3<f32>>, bounds_max: Option<&Vector3<f32>>) -> Vector3<f32> {
+    let mut position = position.clone_owned();
+    if let Some(bounds_min) = bounds_min {
+        position = position.sup(bounds_min);
+    }
+    if let Some(bounds_max) = bounds_max {
+        position = position.inf(bounds_max);
+    }
+    position
+}
+
+// smooth pseudo noise in -1.0 ~ 1.0, the seed picks a different curve per axis
+pub fn get_shake_noise(seed: f32, time: f32) -> f32 {
+    (time + seed).sin() * 0.5 + (time * 2.3 + seed * 1.7).sin() * 0.3 + (time * 4.1 + seed * 3.1).sin() * 0.2
+}
+
+impl CameraShake {
+    pub fn create_camera_shake() -> CameraShake {
+        CameraShake {
+            _trauma: 0.0,
+            _time: 0.0,
+            _offset: Vector3::zeros(),
+            _pitch: 0.0,
+        }
+    }
+
+    pub fn get_trauma(&self) -> f32 {
+        self._trauma
+    }
+
+    pub fn add_trauma(&mut self, trauma: f32) {
+        self._trauma = 0f32.max(1f32.min(self._trauma + trauma));
+    }
+
+    pub fn reset_camera_shake(&mut self) {
+        self._trauma = 0.0;
+        self._offset = Vector3::zeros();
+        self._pitch = 0.0;
+    }
+
+    pub fn update_camera_shake(&mut self, shake_data: &CameraShakeData, delta_time: f32) {
+        if self._trauma <= 0.0 {
+            self._offset = Vector3::zeros();
+            self._pitch = 0.0;
+            return;
+        }
+
+        self._time += delta_time * shake_data._frequency;
+        let shake = self._trauma * self._trauma;
+        self._offset = Vector3::new(
+            shake_data._max_offset.x * shake * get_shake_noise(0.0, self._time),
+            shake_data._max_offset.y * shake * get_shake_noise(10.0, self._time),
+            shake_data._max_offset.z * shake * get_shake_noise(20.0, self._time),
+        );
+        self._pitch = shake_data._max_pitch * shake * get_shake_noise(30.0, self._time);
+        self._trauma = 0f32.max(self._trauma - shake_data._trauma_decay * delta_time);
+    }
+}
+
+impl CameraRig {
+    pub fn create_camera_rig() -> CameraRig {
+        CameraRig {
+            _is_initialized: false,
+            _goal_position: Vector3::zeros(),
+            _focus_position: Vector3::zeros(),
+            _focus_velocity: Vector3::zeros(),
+            _look_ahead: Vector3::zeros(),
+            _look_ahead_velocity: Vector3::zeros(),
+            _camera_shake: CameraShake::create_camera_shake(),
+        }
+    }
+
+    pub fn get_focus_position(&self) -> &Vector3<f32> {
+        &self._focus_position
+    }
+
+    pub fn get_shake_offset(&self) -> &Vector3<f32> {
+        &self._camera_shake._offset
+    }
+
+    pub fn get_shake_pitch(&self) -> f32 {
+        self._camera_shake._pitch
+    }
+
+    pub fn add_camera_trauma(&mut self, trauma: f32) {
+        self._camera_shake.add_trauma(trauma);
+    }
+
+    // snaps to the target on the next update, e.g. after a scene change or a respawn
+    pub fn reset_camera_rig(&mut self) {
+        self._is_initialized = false;
+        self._camera_shake.reset_camera_shake();
+    }
+
+    pub fn update_camera_rig(&mut self, rig_data: &CameraRigData, target_position: &Vector3<f32>, target_velocity: &Vector3<f32>, delta_time: f32) -> &Vector3<f32> {
+        let target_position = target_position + rig_data._target_offset;
+        let bounds_min = rig_data._bounds_min.as_ref();
+        let bounds_max = rig_data._bounds_max.as_ref();
+        self._camera_shake.update_camera_shake(&rig_data._shake, delta_time);
+        if false == self._is_initialized {
+            self._is_initialized = true;
+            self._goal_position = target_position;
+            self._focus_position = clamp_to_bounds(&target_position, bounds_min, bounds_max);
+            self._focus_velocity = Vector3::zeros();
+            self._look_ahead = Vector3::zeros();
+            self._look_ahead_velocity = Vector3::zeros();
+            return &self._focus_position;
+        }
+
+        self._goal_position = apply_dead_zone(&self._goal_position, &target_position, &rig_data._dead_zone);
+        let look_ahead = get_look_ahead(target_velocity, rig_data._look_ahead_time, rig_data._max_look_ahead);
+        self._look_ahead = smooth_damp_vector(&self._look_ahead, &look_ahead, &mut self._look_ahead_velocity, rig_data._look_ahead_smooth_time, delta_time);
+        let focus_goal = clamp_to_bounds(&(self._goal_position + self._look_ahead), bounds_min, bounds_max);
+        let focus_position = smooth_damp_vector(&self._focus_position, &focus_goal, &mut self._focus_velocity, rig_data._smooth_time, delta_time);
+        self._focus_position = clamp_to_bounds(&focus_position, bounds_min, bounds_max);
+        &self._focus_position
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DELTA_TIME: f32 = 1.0 / 60.0;
+
+    #[test]
+    fn test_smooth_damp_converges_without_overshoot() {
+        let mut velocity = 0.0;
+        let mut position = 0.0;
+        for _ in 0..600 {
+            position = smooth_damp(position, 10.0, &mut velocity, 0.2, DELTA_TIME);
+            assert!(position <= 10.0 + 1e-4);
+        }
+        assert!((position - 10.0).abs() < 1e-3);
+
+        // no smoothing snaps to the target
+        let mut velocity = 0.0;
+        assert_eq!(5.0, smooth_damp(1.0, 5.0, &mut velocity, 0.0, DELTA_TIME));
+    }
+
+    #[test]
+    fn test_dead_zone() {
+        let dead_zone = Vector3::new(0.5, 1.0, 0.5);
+        let goal_position = apply_dead_zone(&Vector3::zeros(), &Vector3::new(0.3, -0.9, 0.0), &dead_zone);
+        assert_eq!(Vector3::zeros(), goal_position);
+        // the goal follows just enough to keep the target on the edge of the dead zone
+        let goal_position = apply_dead_zone(&Vector3::zeros(), &Vector3::new(2.0, -3.0, 0.0), &dead_zone);
+        assert_eq!(Vector3::new(1.5, -2.0, 0.0), goal_position);
+    }
+
+    #[test]
+    fn test_look_ahead_clamp() {
+        let look_ahead = get_look_ahead(&Vector3::new(100.0, 50.0, 0.0), 0.3, 2.0);
+        assert!((look_ahead.norm() - 2.0).abs() < 1e-5);
+        assert_eq!(0.0, look_ahead.y);
+        let look_ahead = get_look_ahead(&Vector3::new(1.0, 0.0, 0.0), 0.3, 2.0);
+        assert!((look_ahead.x - 0.3).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_bounds_clamp() {
+        let bounds_min = Vector3::new(-1.0, 0.0, -1.0);
+        let bounds_max = Vector3::new(1.0, 5.0, 1.0);
+        let position = clamp_to_bounds(&Vector3::new(3.0, -2.0, 0.5), Some(&bounds_min), Some(&bounds_max));
+        assert_eq!(Vector3::new(1.0, 0.0, 0.5), position);
+        assert_eq!(Vector3::new(3.0, -2.0, 0.5), clamp_to_bounds(&Vector3::new(3.0, -2.0, 0.5), None, None));
+
+        let mut camera_rig = CameraRig::create_camera_rig();
+        let rig_data = CameraRigData {
+            _bounds_min: Some(bounds_min),
+            _bounds_max: Some(bounds_max),
+            ..Default::default()
+        };
+        for i in 0..300 {
+            let focus_position = camera_rig.update_camera_rig(&rig_data, &Vector3::new(i as f32 * 0.1, 0.0, 0.0), &Vector3::new(6.0, 0.0, 0.0), DELTA_TIME);
+            assert!(focus_position.x <= 1.0 && focus_position.y <= 5.0);
+        }
+    }
+
+    #[test]
+    fn test_camera_shake_trauma_clamps_and_decays() {
+        let shake_data = CameraShakeData::default();
+        let mut camera_shake = CameraShake::create_camera_shake();
+        camera_shake.add_trauma(0.7);
+        camera_shake.add_trauma(0.7);
+        assert_eq!(1.0, camera_shake.get_trauma());
+
+        let mut max_offset = 0f32;
+        for _ in 0..120 {
+            camera_shake.update_camera_shake(&shake_data, DELTA_TIME);
+            max_offset = max_offset.max(camera_shake._offset.norm());
+            assert!(camera_shake._pitch.abs() <= shake_data._max_pitch + 1e-6);
+        }
+        assert!(0.0 < max_offset && max_offset <= shake_data._max_offset.norm() + 1e-6);
+        assert_eq!(0.0, camera_shake.get_trauma());
+        camera_shake.update_camera_shake(&shake_data, DELTA_TIME);
+        assert_eq!(0.0, camera_shake._offset.norm());
+    }
+}
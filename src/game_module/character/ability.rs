@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+
+use nalgebra::Vector3;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AbilityType {
+    DoubleJump,
+    WallJump,
+    Dash,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct AbilityData {
+    // locked abilities have to be unlocked at runtime
+    pub _is_unlocked: bool,
+    pub _max_charges: u32,
+    // minimum time between two uses
+    pub _cooldown: f32,
+    // time to regain one charge, 0.0 refills all charges on landing
+    pub _recharge_time: f32,
+    // jump speed of double jump and wall jump, dash speed of dash
+    pub _speed: f32,
+    // speed away from the wall of a wall jump
+    pub _push_speed: f32,
+    // length of a dash, or how long a wall jump ignores horizontal input
+    pub _duration: f32,
+    // max fall speed while pressing against a wall, 0.0 disables wall slide
+    pub _slide_speed: f32,
+}
+
+// unlocks an ability of the player when the player comes within the radius
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AbilityPickupData {
+    pub _ability_type: AbilityType,
+    pub _position: Vector3<f32>,
+    pub _radius: f32,
+}
+
+pub struct AbilityState {
+    pub _is_unlocked: bool,
+    pub _charges: u32,
+    pub _cooldown_time: f32,
+    pub _recharge_time: f32,
+}
+
+// runtime state of the abilities a character archetype defines in CharacterData::_abilities
+pub struct CharacterAbilities {
+    pub _ability_states: HashMap<AbilityType, AbilityState>,
+}
+
+impl Default for AbilityData {
+    fn default() -> AbilityData {
+        AbilityData {
+            _is_unlocked: true,
+            _max_charges: 1,
+            _cooldown: 0.0,
+            _recharge_time: 0.0,
+            _speed: 10.0,
+            _push_speed: 5.0,
+            _duration: 0.2,
+            _slide_speed: 2.0,
+        }
+    }
+}
+
+impl CharacterAbilities {
+    pub fn create_character_abilities(ability_datas: &HashMap<AbilityType, AbilityData>) -> CharacterAbilities {
+        let mut ability_states: HashMap<AbilityType, AbilityState> = HashMap::new();
+        for (ability_type, ability_data) in ability_datas.iter() {
+            ability_states.insert(*ability_type, AbilityState {
+                _is_unlocked: ability_data._is_unlocked,
+                _charges: ability_data._max_charges,
+                _cooldown_time: 0.0,
+                _recharge_time: 0.0,
+            });
+        }
+        CharacterAbilities {
+            _ability_states: ability_states,
+        }
+    }
+
+    pub fn is_unlocked(&self, ability_type: AbilityType) -> bool {
+        match self._ability_states.get(&ability_type) {
+            Some(ability_state) => ability_state._is_unlocked,
+            None => false,
+        }
+    }
+
+    // returns false when the character archetype doesn't have the ability
+    pub fn set_unlocked(&mut self, ability_type: AbilityType, is_unlocked: bool) -> bool {
+        match self._ability_states.get_mut(&ability_type) {
+            Some(ability_state) => {
+                ability_state._is_unlocked = is_unlocked;
+                true
+            },
+            None => false,
+        }
+    }
+
+    pub fn can_use_ability(&self, ability_type: AbilityType) -> bool {
+        match self._ability_states.get(&ability_type) {
+            Some(ability_state) => ability_state._is_unlocked && 0 < ability_state._charges && ability_state._cooldown_time <= 0.0,
+            None => false,
+        }
+    }
+
+    // spends a charge and returns the ability data when the ability is available
+    pub fn use_ability<'a>(&mut self, ability_type: AbilityType, ability_datas: &'a HashMap<AbilityType, AbilityData>) -> Option<&'a AbilityData> {
+        if false == self.can_use_ability(ability_type) {
+            return None;
+        }
+
+        let ability_data = ability_datas.get(&ability_type)?;
+        let ability_state = self._ability_states.get_mut(&ability_type).unwrap();
+        ability_state._charges -= 1;
+        ability_state._cooldown_time = ability_data._cooldown;
+        Some(ability_data)
+    }
+
+    pub fn update_abilities(&mut self, ability_datas: &HashMap<AbilityType, AbilityData>, is_ground: bool, delta_time: f32) {
+        for (ability_type, ability_state) in self._ability_states.iter_mut() {
+            let ability_data = match ability_datas.get(ability_type) {
+                Some(ability_data) => ability_data,
+                None => continue,
+            };
+
+            ability_state._cooldown_time = 0f32.max(ability_state._cooldown_time - delta_time);
+            if ability_data._max_charges <= ability_state._charges {
+                ability_state._recharge_time = 0.0;
+            } else if 0.0 < ability_data._recharge_time {
+                ability_state._recharge_time += delta_time;
+                if ability_data._recharge_time <= ability_state._recharge_time {
+                    ability_state._recharge_time = 0.0;
+                    ability_state._charges += 1;
+                }
+            } else if is_ground {
+                ability_state._charges = ability_data._max_charges;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DELTA_TIME: f32 = 0.1;
+
+    fn create_ability_datas(ability_type: AbilityType, ability_data: AbilityData) -> HashMap<AbilityType, AbilityData> {
+        let mut ability_datas = HashMap::new();
+        ability_datas.insert(ability_type, ability_data);
+        ability_datas
+    }
+
+    #[test]
+    fn test_locked_ability() {
+        let ability_datas = create_ability_datas(AbilityType::DoubleJump, AbilityData {
+            _is_unlocked: false,
+            ..Default::default()
+        });
+        let mut abilities = CharacterAbilities::create_character_abilities(&ability_datas);
+        assert!(abilities.use_ability(AbilityType::DoubleJump, &ability_datas).is_none());
+        assert!(abilities.set_unlocked(AbilityType::DoubleJump, true));
+        assert!(abilities.use_ability(AbilityType::DoubleJump, &ability_datas).is_some());
+        // the archetype has no dash to unlock
+        assert!(false == abilities.set_unlocked(AbilityType::Dash, true));
+        assert!(false == abilities.can_use_ability(AbilityType::Dash));
+    }
+
+    #[test]
+    fn test_charges_refill_on_landing() {
+        let ability_datas = create_ability_datas(AbilityType::DoubleJump, AbilityData {
+            _max_charges: 2,
+            ..Default::default()
+        });
+        let mut abilities = CharacterAbilities::create_character_abilities(&ability_datas);
+        assert!(abilities.use_ability(AbilityType::DoubleJump, &ability_datas).is_some());
+        assert!(abilities.use_ability(AbilityType::DoubleJump, &ability_datas).is_some());
+        assert!(abilities.use_ability(AbilityType::DoubleJump, &ability_datas).is_none());
+        abilities.update_abilities(&ability_datas, false, DELTA_TIME);
+        assert!(false == abilities.can_use_ability(AbilityType::DoubleJump));
+        abilities.update_abilities(&ability_datas, true, DELTA_TIME);
+        assert_eq!(2, abilities._ability_states[&AbilityType::DoubleJump]._charges);
+    }
+
+    #[test]
+    fn test_cooldown_and_recharge() {
+        let ability_datas = create_ability_datas(AbilityType::Dash, AbilityData {
+            _max_charges: 2,
+            _cooldown: 0.25,
+            _recharge_time: 1.0,
+            ..Default::default()
+        });
+        let mut abilities = CharacterAbilities::create_character_abilities(&ability_datas);
+        assert!(abilities.use_ability(AbilityType::Dash, &ability_datas).is_some());
+        // a charge is left but the cooldown blocks it
+        abilities.update_abilities(&ability_datas, true, DELTA_TIME);
+        assert!(false == abilities.can_use_ability(AbilityType::Dash));
+        abilities.update_abilities(&ability_datas, true, DELTA_TIME * 2.0);
+        assert!(abilities.use_ability(AbilityType::Dash, &ability_datas).is_some());
+
+        // a recharge time ignores landing and regains one charge at a time
+        for _ in 0..5 {
+            abilities.update_abilities(&ability_datas, true, DELTA_TIME);
+        }
+        assert!(false == abilities.can_use_ability(AbilityType::Dash));
+        for _ in 0..6 {
+            abilities.update_abilities(&ability_datas, true, DELTA_TIME);
+        }
+        assert_eq!(1, abilities._ability_states[&AbilityType::Dash]._charges);
+        for _ in 0..11 {
+            abilities.update_abilities(&ability_datas, true, DELTA_TIME);
+        }
+        assert_eq!(2, abilities._ability_states[&AbilityType::Dash]._charges);
+    }
+}
@@ -0,0 +1,382 @@
+use std::collections::HashMap;
+
+use nalgebra::Vector3;
+use serde::{Deserialize, Serialize};
+
+use crate::game_module::character::character::*;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum AnimationCondition {
+    Always,
+    IsGround,
+    IsAir,
+    IsMoving,
+    IsStopped,
+    IsRising,
+    IsFalling,
+    IsDead,
+    Action(ActionAnimationState),
+    ActionStarted,
+    IsHitStun,
+    IsNotHitStun,
+    HitStarted,
+    AnimationEnd,
+}
+
+// all conditions must hold for the transition to be taken
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct AnimationTransitionData {
+    pub _next_state: String,
+    pub _conditions: Vec<AnimationCondition>,
+    pub _blend_time: f32,
+}
+
+// emitted once when the animation play time crosses _time
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+#[serde(default)]
+pub struct AnimationNotifyData {
+    pub _time: f32,
+    pub _event: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct AnimationStateData {
+    // name in CharacterData::_animation_set, an empty name plays nothing
+    pub _animation: String,
+    // plays the animation of the current attack combo step instead of _animation
+    pub _use_attack_animation: bool,
+    pub _loop: bool,
+    pub _speed: f32,
+    pub _fade_out_time: f32,
+    pub _force_animation_setting: bool,
+    pub _notifies: Vec<AnimationNotifyData>,
+    pub _transitions: Vec<AnimationTransitionData>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+#[serde(default)]
+pub struct AnimationLayerData {
+    pub _initial_state: String,
+    pub _any_state_transitions: Vec<AnimationTransitionData>,
+    pub _states: HashMap<String, AnimationStateData>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct AnimationGraphData {
+    pub _move_threshold: f32,
+    pub _base_layer: AnimationLayerData,
+    pub _additive_layer: AnimationLayerData,
+}
+
+// Controller values the graph conditions are evaluated against.
+#[derive(Clone, Debug)]
+pub struct AnimationParameters {
+    pub _velocity: Vector3<f32>,
+    pub _is_ground: bool,
+    pub _is_dead: bool,
+    pub _action: ActionAnimationState,
+    pub _is_action_started: bool,
+    pub _is_hit_stun: bool,
+    pub _is_hit_started: bool,
+    pub _move_threshold: f32,
+}
+
+pub struct AnimationLayerStateMachine {
+    pub _state: String,
+    pub _state_time: f32,
+    // animation play time the notifies are collected up to, None until the first update after entering a state
+    pub _play_time: Option<f32>,
+}
+
+impl Default for AnimationTransitionData {
+    fn default() -> AnimationTransitionData {
+        AnimationTransitionData {
+            _next_state: String::default(),
+            _conditions: Vec::new(),
+            _blend_time: 0.1,
+        }
+    }
+}
+
+impl Default for AnimationStateData {
+    fn default() -> AnimationStateData {
+        AnimationStateData {
+            _animation: String::default(),
+            _use_attack_animation: false,
+            _loop: true,
+            _speed: 1.0,
+            _fade_out_time: 0.0,
+            _force_animation_setting: false,
+            _notifies: Vec::new(),
+            _transitions: Vec::new(),
+        }
+    }
+}
+
+impl Default for AnimationGraphData {
+    fn default() -> AnimationGraphData {
+        let transition = |next_state: &str, conditions: &[AnimationCondition], blend_time: f32| AnimationTransitionData {
+            _next_state: String::from(next_state),
+            _conditions: conditions.to_vec(),
+            _blend_time: blend_time,
+        };
+        let notify = |time: f32, event: &str| AnimationNotifyData {
+            _time: time,
+            _event: String::from(event),
+        };
+
+        let mut base_states: HashMap<String, AnimationStateData> = HashMap::new();
+        base_states.insert(String::from(ANIMATION_IDLE), AnimationStateData {
+            _animation: String::from(ANIMATION_IDLE),
+            _transitions: vec![
+                transition(ANIMATION_JUMP, &[AnimationCondition::IsAir], 0.1),
+                transition(ANIMATION_WALK, &[AnimationCondition::IsMoving], 0.1),
+            ],
+            ..Default::default()
+        });
+        base_states.insert(String::from(ANIMATION_WALK), AnimationStateData {
+            _animation: String::from(ANIMATION_WALK),
+            _notifies: vec![
+                notify(0.0, ANIMATION_EVENT_FOOTSTEP),
+                notify(0.5, ANIMATION_EVENT_FOOTSTEP),
+            ],
+            _transitions: vec![
+                transition(ANIMATION_JUMP, &[AnimationCondition::IsAir], 0.1),
+                transition(ANIMATION_IDLE, &[AnimationCondition::IsStopped], 0.1),
+            ],
+            ..Default::default()
+        });
+        base_states.insert(String::from(ANIMATION_JUMP), AnimationStateData {
+            _animation: String::from(ANIMATION_JUMP),
+            _loop: false,
+            _notifies: vec![notify(0.0, "play_sound:jump_start")],
+            _transitions: vec![
+                transition(ANIMATION_WALK, &[AnimationCondition::IsGround, AnimationCondition::IsMoving], 0.1),
+                transition(ANIMATION_IDLE, &[AnimationCondition::IsGround, AnimationCondition::IsStopped], 0.1),
+            ],
+            ..Default::default()
+        });
+        base_states.insert(String::from(ANIMATION_HIT), AnimationStateData {
+            _animation: String::from(ANIMATION_HIT),
+            _loop: false,
+            _force_animation_setting: true,
+            _transitions: vec![
+                transition(ANIMATION_HIT, &[AnimationCondition::HitStarted], 0.05),
+                transition(ANIMATION_IDLE, &[AnimationCondition::IsNotHitStun], 0.1),
+            ],
+            ..Default::default()
+        });
+        base_states.insert(String::from(ANIMATION_DEATH), AnimationStateData {
+            _animation: String::from(ANIMATION_DEATH),
+            _loop: false,
+            _force_animation_setting: true,
+            ..Default::default()
+        });
+
+        let mut additive_states: HashMap<String, AnimationStateData> = HashMap::new();
+        additive_states.insert(String::from(ANIMATION_STATE_NONE), AnimationStateData {
+            _transitions: vec![
+                transition(ANIMATION_ATTACK, &[AnimationCondition::Action(ActionAnimationState::ATTACK)], 0.1),
+            ],
+            ..Default::default()
+        });
+        additive_states.insert(String::from(ANIMATION_ATTACK), AnimationStateData {
+            _animation: String::from(ANIMATION_ATTACK),
+            _use_attack_animation: true,
+            _loop: false,
+            _fade_out_time: 0.1,
+            _force_animation_setting: true,
+            _notifies: vec![
+                notify(0.15, "play_sound:swoosh"),
+                notify(0.15, ANIMATION_EVENT_HIT_WINDOW_START),
+                notify(0.3, ANIMATION_EVENT_HIT_WINDOW_END),
+            ],
+            _transitions: vec![
+                transition(ANIMATION_ATTACK, &[AnimationCondition::ActionStarted], 0.1),
+                transition(ANIMATION_STATE_NONE, &[AnimationCondition::Action(ActionAnimationState::NONE)], 0.1),
+            ],
+            ..Default::default()
+        });
+
+        AnimationGraphData {
+            _move_threshold: 0.01,
+            _base_layer: AnimationLayerData {
+                _initial_state: String::from(ANIMATION_IDLE),
+                _any_state_transitions: vec![
+                    transition(ANIMATION_DEATH, &[AnimationCondition::IsDead], 0.1),
+                    transition(ANIMATION_HIT, &[AnimationCondition::HitStarted], 0.05),
+                ],
+                _states: base_states,
+            },
+            _additive_layer: AnimationLayerData {
+                _initial_state: String::from(ANIMATION_STATE_NONE),
+                _any_state_transitions: vec![transition(ANIMATION_STATE_NONE, &[AnimationCondition::IsDead], 0.1)],
+                _states: additive_states,
+            },
+        }
+    }
+}
+
+impl AnimationParameters {
+    pub fn check_condition(&self, condition: AnimationCondition, is_animation_end: bool) -> bool {
+        let is_moving = self._move_threshold < self._velocity.x.hypot(self._velocity.z);
+        match condition {
+            AnimationCondition::Always => true,
+            AnimationCondition::IsGround => self._is_ground,
+            AnimationCondition::IsAir => false == self._is_ground,
+            AnimationCondition::IsMoving => is_moving,
+            AnimationCondition::IsStopped => false == is_moving,
+            AnimationCondition::IsRising => 0.0 < self._velocity.y,
+            AnimationCondition::IsFalling => self._velocity.y < 0.0,
+            AnimationCondition::IsDead => self._is_dead,
+            AnimationCondition::Action(action) => action == self._action,
+            AnimationCondition::ActionStarted => self._is_action_started,
+            AnimationCondition::IsHitStun => self._is_hit_stun,
+            AnimationCondition::IsNotHitStun => false == self._is_hit_stun,
+            AnimationCondition::HitStarted => self._is_hit_started,
+            AnimationCondition::AnimationEnd => is_animation_end,
+        }
+    }
+
+    pub fn check_transition(&self, transition: &AnimationTransitionData, is_animation_end: bool) -> bool {
+        transition._conditions.iter().all(|condition| self.check_condition(*condition, is_animation_end))
+    }
+}
+
+impl AnimationLayerStateMachine {
+    pub fn create_animation_layer_state_machine(layer_data: &AnimationLayerData) -> AnimationLayerStateMachine {
+        AnimationLayerStateMachine {
+            _state: layer_data._initial_state.clone(),
+            _state_time: 0.0,
+            _play_time: None,
+        }
+    }
+
+    pub fn get_state(&self) -> &str {
+        self._state.as_str()
+    }
+
+    pub fn collect_animation_events(state_data: &AnimationStateData, prev_play_time: f32, play_time: f32, animation_events: &mut Vec<String>) {
+        for notify in state_data._notifies.iter() {
+            let is_crossed = if prev_play_time <= play_time {
+                prev_play_time <= notify._time && notify._time < play_time
+            } else {
+                // wrapped around the end of the loop
+                prev_play_time <= notify._time || notify._time < play_time
+            };
+
+            if is_crossed {
+                animation_events.push(notify._event.clone());
+            }
+        }
+    }
+
+    // returns the entered transition, the current state may be re-entered.
+    // prev_play_time and play_time are the play times of the layer's animation over the last render update,
+    // notifies follow the animation itself so they stay in sync with its real length and speed
+    pub fn update_animation_layer_state_machine<'a>(
+        &mut self,
+        layer_data: &'a AnimationLayerData,
+        parameters: &AnimationParameters,
+        is_animation_end: bool,
+        prev_play_time: f32,
+        play_time: f32,
+        delta_time: f32,
+        animation_events: &mut Vec<String>
+    ) -> Option<&'a AnimationTransitionData> {
+        let any_state_transition = layer_data._any_state_transitions.iter().find(|transition| {
+            transition._next_state != self._state && parameters.check_transition(transition, is_animation_end)
+        });
+        let transition = match any_state_transition {
+            Some(transition) => Some(transition),
+            None => match layer_data._states.get(&self._state) {
+                Some(state_data) => state_data._transitions.iter().find(|transition| parameters.check_transition(transition, is_animation_end)),
+                None => None,
+            },
+        };
+
+        if let Some(transition) = transition {
+            // the animation of the new state starts after this update
+            self._state = transition._next_state.clone();
+            self._state_time = 0.0;
+            self._play_time = None;
+        } else if let Some(state_data) = layer_data._states.get(&self._state) {
+            // several ticks can share one render update, each play time range is collected once
+            self._state_time += delta_time * state_data._speed;
+            let prev_play_time = self._play_time.unwrap_or(prev_play_time);
+            AnimationLayerStateMachine::collect_animation_events(state_data, prev_play_time, play_time, animation_events);
+            self._play_time = Some(play_time);
+        }
+        transition
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_parameters(is_dead: bool) -> AnimationParameters {
+        AnimationParameters {
+            _velocity: Vector3::zeros(),
+            _is_ground: true,
+            _is_dead: is_dead,
+            _action: ActionAnimationState::NONE,
+            _is_action_started: false,
+            _is_hit_stun: false,
+            _is_hit_started: false,
+            _move_threshold: 0.01,
+        }
+    }
+
+    fn update(state_machine: &mut AnimationLayerStateMachine, layer_data: &AnimationLayerData, is_dead: bool, prev_play_time: f32, play_time: f32) -> Vec<String> {
+        let mut animation_events: Vec<String> = Vec::new();
+        state_machine.update_animation_layer_state_machine(layer_data, &create_parameters(is_dead), false, prev_play_time, play_time, 1.0 / 60.0, &mut animation_events);
+        animation_events
+    }
+
+    // the default graph states without their own transitions
+    fn create_layer_data() -> AnimationLayerData {
+        let mut layer_data = AnimationGraphData::default()._base_layer;
+        for state_data in layer_data._states.values_mut() {
+            state_data._transitions.clear();
+        }
+        layer_data
+    }
+
+    #[test]
+    fn test_notifies_follow_the_animation_play_time() {
+        let layer_data = create_layer_data();
+        let mut state_machine = AnimationLayerStateMachine::create_animation_layer_state_machine(&layer_data);
+        state_machine._state = String::from(ANIMATION_WALK);
+
+        // the first update syncs to the render update, later ones continue from the last collected time
+        assert_eq!(vec![ANIMATION_EVENT_FOOTSTEP], update(&mut state_machine, &layer_data, false, 0.0, 0.1));
+        assert!(update(&mut state_machine, &layer_data, false, 0.1, 0.45).is_empty());
+        assert_eq!(vec![ANIMATION_EVENT_FOOTSTEP], update(&mut state_machine, &layer_data, false, 0.45, 0.6));
+
+        // a second tick in the same render update collects nothing
+        assert!(update(&mut state_machine, &layer_data, false, 0.45, 0.6).is_empty());
+
+        // a render update without a tick is collected by the next tick, the loop wraps at the clip's real length
+        assert_eq!(vec![ANIMATION_EVENT_FOOTSTEP], update(&mut state_machine, &layer_data, false, 0.9, 0.05));
+    }
+
+    #[test]
+    fn test_entering_a_state_skips_the_previous_animation() {
+        let layer_data = create_layer_data();
+        let mut state_machine = AnimationLayerStateMachine::create_animation_layer_state_machine(&layer_data);
+        state_machine._state = String::from(ANIMATION_JUMP);
+        update(&mut state_machine, &layer_data, false, 0.0, 0.3);
+
+        // enters death, the play times still belong to the jump animation
+        assert!(update(&mut state_machine, &layer_data, true, 0.3, 0.4).is_empty());
+        assert_eq!(ANIMATION_DEATH, state_machine.get_state());
+
+        state_machine._state = String::from(ANIMATION_JUMP);
+        state_machine._play_time = None;
+        assert_eq!(vec!["play_sound:jump_start"], update(&mut state_machine, &layer_data, false, 0.0, 0.02));
+    }
+}
@@ -0,0 +1,222 @@
+use nalgebra::Vector3;
+use rust_engine_3d::utilities::bounding_box::BoundingBox;
+use serde::{Deserialize, Serialize};
+
+use crate::game_module::character::character::ANIMATION_ATTACK;
+
+// attack shapes in the attacker's local space, x is forward, y is up and z is to the side
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum AttackShape {
+    Box { _half_extents: Vector3<f32> },
+    Sphere { _radius: f32 },
+    Arc { _radius: f32, _angle: f32, _height: f32 },
+}
+
+// active time range measured from the start of the swing
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+#[serde(default)]
+pub struct AttackWindowData {
+    pub _start_time: f32,
+    pub _end_time: f32,
+}
+
+// one step of a combo chain, times are measured from the start of the step
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct AttackComboStepData {
+    // key in CharacterData::_attacks and in the animation set
+    pub _attack_name: String,
+    // attack presses from this time on are buffered for the next step
+    pub _buffer_start_time: f32,
+    // a buffered press starts the next step at this time
+    pub _cancel_time: f32,
+    // attack presses from this time on restart the chain
+    pub _combo_end_time: f32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct AttackData {
+    pub _shape: AttackShape,
+    pub _offset: Vector3<f32>,
+    // when empty the hit_window_start / hit_window_end animation events are used
+    pub _active_windows: Vec<AttackWindowData>,
+    pub _damage: i32,
+    // horizontal and vertical speed given to the victim
+    pub _knockback: Vector3<f32>,
+    // camera trauma added on hit, 0.0 ~ 1.0
+    pub _camera_shake: f32,
+    // seconds the simulation freezes on hit
+    pub _hit_stop_time: f32,
+}
+
+impl Default for AttackData {
+    fn default() -> AttackData {
+        AttackData {
+            _shape: AttackShape::Sphere { _radius: 0.5 },
+            _offset: Vector3::new(1.0, 1.0, 0.0),
+            _active_windows: Vec::new(),
+            _damage: 10,
+            _knockback: Vector3::new(2.0, 3.0, 0.0),
+            _camera_shake: 0.0,
+            _hit_stop_time: 0.0,
+        }
+    }
+}
+
+impl Default for AttackComboStepData {
+    fn default() -> AttackComboStepData {
+        AttackComboStepData {
+            _attack_name: String::from(ANIMATION_ATTACK),
+            _buffer_start_time: 0.0,
+            _cancel_time: 0.15,
+            _combo_end_time: 0.15,
+        }
+    }
+}
+
+impl AttackData {
+    pub fn is_active_time(&self, attack_time: f32) -> Option<bool> {
+        if self._active_windows.is_empty() {
+            return None;
+        }
+        Some(self._active_windows.iter().any(|window| window._start_time <= attack_time && attack_time < window._end_time))
+    }
+
+    pub fn get_attack_center(&self, position: &Vector3<f32>, forward: &Vector3<f32>) -> Vector3<f32> {
+        let side = Vector3::y().cross(forward);
+        position + forward * self._offset.x + Vector3::y() * self._offset.y + side * self._offset.z
+    }
+
+    // world space half extents of the box around the yaw rotated attack shape
+    pub fn get_attack_extents(&self, forward: &Vector3<f32>) -> Vector3<f32> {
+        match &self._shape {
+            AttackShape::Box { _half_extents } => {
+                let side = Vector3::y().cross(forward);
+                Vector3::new(
+                    forward.x.abs() * _half_extents.x + side.x.abs() * _half_extents.z,
+                    _half_extents.y,
+                    forward.z.abs() * _half_extents.x + side.z.abs() * _half_extents.z,
+                )
+            },
+            AttackShape::Sphere { _radius } => Vector3::new(*_radius, *_radius, *_radius),
+            AttackShape::Arc { _radius, _height, .. } => Vector3::new(*_radius, _height * 0.5, *_radius),
+        }
+    }
+
+    // broadphase region of the attack, returns min and max
+    pub fn get_attack_region(&self, position: &Vector3<f32>, forward: &Vector3<f32>) -> (Vector3<f32>, Vector3<f32>) {
+        let center = self.get_attack_center(position, forward);
+        let extents = self.get_attack_extents(forward);
+        (center - extents, center + extents)
+    }
+
+    pub fn collide_attack(&self, position: &Vector3<f32>, forward: &Vector3<f32>, target_bound_box: &BoundingBox) -> bool {
+        let center = self.get_attack_center(position, forward);
+        match &self._shape {
+            AttackShape::Box { .. } => {
+                let (min, max) = self.get_attack_region(position, forward);
+                min.x <= target_bound_box._max.x && target_bound_box._min.x <= max.x &&
+                    min.y <= target_bound_box._max.y && target_bound_box._min.y <= max.y &&
+                    min.z <= target_bound_box._max.z && target_bound_box._min.z <= max.z
+            },
+            AttackShape::Sphere { _radius } => {
+                let closest_point = center.sup(&target_bound_box._min).inf(&target_bound_box._max);
+                (closest_point - center).norm() <= *_radius
+            },
+            AttackShape::Arc { _radius, _angle, _height } => {
+                let half_height = _height * 0.5;
+                if center.y + half_height < target_bound_box._min.y || target_bound_box._max.y < center.y - half_height {
+                    return false;
+                }
+
+                let mut closest_point = center.sup(&target_bound_box._min).inf(&target_bound_box._max);
+                closest_point.y = center.y;
+                let to_target = closest_point - center;
+                let distance = to_target.norm();
+                if *_radius < distance {
+                    return false;
+                }
+
+                // a target overlapping the arc origin is always inside
+                if distance <= f32::EPSILON {
+                    return true;
+                }
+                let cos_half_angle = (_angle.to_radians() * 0.5).cos();
+                cos_half_angle <= forward.dot(&to_target) / distance
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_attack_data(shape: AttackShape) -> AttackData {
+        AttackData {
+            _shape: shape,
+            _offset: Vector3::zeros(),
+            ..Default::default()
+        }
+    }
+
+    // a small box around the point, it behaves like the point itself
+    fn create_target(x: f32, y: f32, z: f32) -> BoundingBox {
+        let half_extents = Vector3::new(0.001, 0.001, 0.001);
+        let position = Vector3::new(x, y, z);
+        BoundingBox::create_bounding_box(&(position - half_extents), &(position + half_extents))
+    }
+
+    #[test]
+    fn test_arc_half_angle() {
+        let attack_data = create_attack_data(AttackShape::Arc { _radius: 2.0, _angle: 90.0, _height: 2.0 });
+        let position = Vector3::zeros();
+        let forward = Vector3::x();
+        for (angle, is_hit) in [(43.0f32, true), (47.0f32, false), (-43.0f32, true), (-47.0f32, false)] {
+            let (sin, cos) = angle.to_radians().sin_cos();
+            let target = create_target(cos * 1.5, 0.0, sin * 1.5);
+            assert_eq!(is_hit, attack_data.collide_attack(&position, &forward, &target), "angle: {}", angle);
+        }
+        // out of radius and out of height
+        assert!(false == attack_data.collide_attack(&position, &forward, &create_target(2.1, 0.0, 0.0)));
+        assert!(false == attack_data.collide_attack(&position, &forward, &create_target(1.0, 1.1, 0.0)));
+        assert!(attack_data.collide_attack(&position, &forward, &create_target(1.0, 0.9, 0.0)));
+    }
+
+    #[test]
+    fn test_sphere_radius() {
+        let attack_data = create_attack_data(AttackShape::Sphere { _radius: 1.0 });
+        let position = Vector3::zeros();
+        let forward = Vector3::x();
+        let create_box = |min: Vector3<f32>| BoundingBox::create_bounding_box(&min, &(min + Vector3::new(1.0, 1.0, 1.0)));
+        assert!(attack_data.collide_attack(&position, &forward, &create_box(Vector3::new(0.99, -0.5, -0.5))));
+        assert!(false == attack_data.collide_attack(&position, &forward, &create_box(Vector3::new(1.01, -0.5, -0.5))));
+        // the closest corner is measured, not the closest face
+        assert!(attack_data.collide_attack(&position, &forward, &create_box(Vector3::new(0.7, 0.7, -0.5))));
+        assert!(false == attack_data.collide_attack(&position, &forward, &create_box(Vector3::new(0.72, 0.72, -0.5))));
+    }
+
+    #[test]
+    fn test_box_with_rotated_attacker() {
+        // in MovementMode::Free3D the attacker can face any direction on the ground plane
+        let attack_data = AttackData {
+            _shape: AttackShape::Box { _half_extents: Vector3::new(1.0, 0.5, 0.25) },
+            _offset: Vector3::new(1.0, 0.0, 0.0),
+            ..Default::default()
+        };
+        let position = Vector3::zeros();
+        let target_on_x_axis = create_target(1.8, 0.0, 0.0);
+        assert!(attack_data.collide_attack(&position, &Vector3::x(), &target_on_x_axis));
+
+        let forward = Vector3::new(1.0, 0.0, 1.0).normalize();
+        let (min, max) = attack_data.get_attack_region(&position, &forward);
+        let center = attack_data.get_attack_center(&position, &forward);
+        assert!((center - forward).norm() < 1e-5);
+        assert!(((max - min) * 0.5 - Vector3::new(1.25 * forward.x, 0.5, 1.25 * forward.z)).norm() < 1e-5);
+        assert!(attack_data.collide_attack(&position, &forward, &create_target(1.4, 0.0, 1.4)));
+        assert!(false == attack_data.collide_attack(&position, &forward, &target_on_x_axis));
+        assert!(false == attack_data.collide_attack(&position, &forward, &create_target(-0.5, 0.0, -0.5)));
+        assert!(false == attack_data.collide_attack(&position, &forward, &create_target(1.4, 0.6, 1.4)));
+    }
+}
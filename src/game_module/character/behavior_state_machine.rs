@@ -0,0 +1,428 @@
+use std::collections::HashMap;
+
+use nalgebra::Vector3;
+use serde::{Deserialize, Serialize};
+
+use crate::game_module::character::character::MovementMode;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BehaviorState {
+    Idle,
+    Patrol,
+    Chase,
+    Attack,
+    Flee,
+    ReturnHome,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum BehaviorCondition {
+    Always,
+    StateTimeout,
+    TargetDetected,
+    TargetLost,
+    TargetInAttackRange,
+    TargetOutOfAttackRange,
+    LowHp,
+    FarFromHome,
+    AtHome,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct BehaviorTransition {
+    pub _condition: BehaviorCondition,
+    pub _next_state: BehaviorState,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+#[serde(default)]
+pub struct BehaviorStateData {
+    pub _duration: f32,
+    pub _transitions: Vec<BehaviorTransition>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct BehaviorData {
+    pub _initial_state: BehaviorState,
+    pub _detection_range: f32,
+    pub _lose_target_range: f32,
+    pub _attack_range: f32,
+    pub _attack_cooldown: f32,
+    pub _patrol_distance: f32,
+    pub _leash_range: f32,
+    pub _home_tolerance: f32,
+    pub _flee_hp_ratio: f32,
+    // dashes toward a target farther than this while chasing, 0.0 never dashes
+    pub _dash_range: f32,
+    pub _states: HashMap<BehaviorState, BehaviorStateData>,
+}
+
+// What the npc knows about the world this tick.
+#[derive(Clone, Debug)]
+pub struct BehaviorPerception {
+    pub _position: Vector3<f32>,
+    pub _target_position: Option<Vector3<f32>>,
+    pub _hp_ratio: f32,
+    pub _movement_mode: MovementMode,
+}
+
+// What the npc wants to do this tick. A zero direction means no request.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BehaviorCommand {
+    pub _move_direction: Vector3<f32>,
+    pub _face_direction: Vector3<f32>,
+    pub _attack: bool,
+    pub _dash: bool,
+}
+
+pub struct BehaviorStateMachine {
+    pub _state: BehaviorState,
+    pub _state_time: f32,
+    pub _home_position: Vector3<f32>,
+    // free 3d patrols along this horizontal axis, the side scroller along x
+    pub _patrol_axis: Vector3<f32>,
+    pub _patrol_direction: f32,
+    pub _attack_cooldown: f32,
+}
+
+impl Default for BehaviorTransition {
+    fn default() -> BehaviorTransition {
+        BehaviorTransition {
+            _condition: BehaviorCondition::Always,
+            _next_state: BehaviorState::Idle,
+        }
+    }
+}
+
+impl Default for BehaviorData {
+    fn default() -> BehaviorData {
+        let transition = |condition: BehaviorCondition, next_state: BehaviorState| BehaviorTransition {
+            _condition: condition,
+            _next_state: next_state,
+        };
+        let mut states: HashMap<BehaviorState, BehaviorStateData> = HashMap::new();
+        states.insert(BehaviorState::Idle, BehaviorStateData {
+            _duration: 1.0,
+            _transitions: vec![
+                transition(BehaviorCondition::TargetDetected, BehaviorState::Chase),
+                transition(BehaviorCondition::StateTimeout, BehaviorState::Patrol),
+            ],
+        });
+        states.insert(BehaviorState::Patrol, BehaviorStateData {
+            _duration: 0.0,
+            _transitions: vec![
+                transition(BehaviorCondition::TargetDetected, BehaviorState::Chase),
+            ],
+        });
+        states.insert(BehaviorState::Chase, BehaviorStateData {
+            _duration: 0.0,
+            _transitions: vec![
+                transition(BehaviorCondition::LowHp, BehaviorState::Flee),
+                transition(BehaviorCondition::TargetInAttackRange, BehaviorState::Attack),
+                transition(BehaviorCondition::TargetLost, BehaviorState::ReturnHome),
+                transition(BehaviorCondition::FarFromHome, BehaviorState::ReturnHome),
+            ],
+        });
+        states.insert(BehaviorState::Attack, BehaviorStateData {
+            _duration: 0.0,
+            _transitions: vec![
+                transition(BehaviorCondition::LowHp, BehaviorState::Flee),
+                transition(BehaviorCondition::TargetOutOfAttackRange, BehaviorState::Chase),
+            ],
+        });
+        states.insert(BehaviorState::Flee, BehaviorStateData {
+            _duration: 0.0,
+            _transitions: vec![
+                transition(BehaviorCondition::TargetLost, BehaviorState::ReturnHome),
+            ],
+        });
+        states.insert(BehaviorState::ReturnHome, BehaviorStateData {
+            _duration: 0.0,
+            _transitions: vec![
+                transition(BehaviorCondition::AtHome, BehaviorState::Idle),
+            ],
+        });
+
+        BehaviorData {
+            _initial_state: BehaviorState::Patrol,
+            _detection_range: 5.0,
+            _lose_target_range: 8.0,
+            _attack_range: 1.5,
+            _attack_cooldown: 1.5,
+            _patrol_distance: 3.0,
+            _leash_range: 12.0,
+            _home_tolerance: 0.25,
+            _flee_hp_ratio: 0.0,
+            _dash_range: 0.0,
+            _states: states,
+        }
+    }
+}
+
+impl BehaviorStateMachine {
+    pub fn create_behavior_state_machine(initial_state: BehaviorState, home_position: &Vector3<f32>) -> BehaviorStateMachine {
+        BehaviorStateMachine {
+            _state: initial_state,
+            _state_time: 0.0,
+            _home_position: home_position.clone_owned(),
+            _patrol_axis: Vector3::x(),
+            _patrol_direction: 1.0,
+            _attack_cooldown: 0.0,
+        }
+    }
+
+    pub fn set_patrol_axis(&mut self, patrol_axis: &Vector3<f32>) {
+        let patrol_axis = Vector3::new(patrol_axis.x, 0.0, patrol_axis.z);
+        if f32::EPSILON < patrol_axis.norm() {
+            self._patrol_axis = patrol_axis.normalize();
+        }
+    }
+
+    pub fn get_state(&self) -> BehaviorState {
+        self._state
+    }
+
+    pub fn set_state(&mut self, state: BehaviorState) {
+        self._state = state;
+        self._state_time = 0.0;
+    }
+
+    pub fn check_condition(&self, behavior_data: &BehaviorData, perception: &BehaviorPerception, condition: BehaviorCondition) -> bool {
+        let target_distance: Option<f32> = perception._target_position.as_ref().map(|target_position| (target_position - perception._position).norm());
+        let home_distance = (self._home_position - perception._position).norm();
+        match condition {
+            BehaviorCondition::Always => true,
+            BehaviorCondition::StateTimeout => match behavior_data._states.get(&self._state) {
+                Some(state_data) => 0.0 < state_data._duration && state_data._duration <= self._state_time,
+                None => false,
+            },
+            BehaviorCondition::TargetDetected => match target_distance {
+                Some(distance) => distance <= behavior_data._detection_range,
+                None => false,
+            },
+            BehaviorCondition::TargetLost => match target_distance {
+                Some(distance) => behavior_data._lose_target_range < distance,
+                None => true,
+            },
+            BehaviorCondition::TargetInAttackRange => match target_distance {
+                Some(distance) => distance <= behavior_data._attack_range,
+                None => false,
+            },
+            BehaviorCondition::TargetOutOfAttackRange => match target_distance {
+                Some(distance) => behavior_data._attack_range < distance,
+                None => true,
+            },
+            BehaviorCondition::LowHp => perception._hp_ratio <= behavior_data._flee_hp_ratio,
+            BehaviorCondition::FarFromHome => behavior_data._leash_range < home_distance,
+            BehaviorCondition::AtHome => home_distance <= behavior_data._home_tolerance,
+        }
+    }
+
+    pub fn update_transitions(&mut self, behavior_data: &BehaviorData, perception: &BehaviorPerception) {
+        if let Some(state_data) = behavior_data._states.get(&self._state) {
+            for transition in state_data._transitions.iter() {
+                if self.check_condition(behavior_data, perception, transition._condition) {
+                    self.set_state(transition._next_state);
+                    break;
+                }
+            }
+        }
+    }
+
+    pub fn update_behavior_state_machine(&mut self, behavior_data: &BehaviorData, perception: &BehaviorPerception, delta_time: f32) -> BehaviorCommand {
+        self._state_time += delta_time;
+        self._attack_cooldown = 0f32.max(self._attack_cooldown - delta_time);
+        self.update_transitions(behavior_data, perception);
+
+        // horizontal direction, the controller projects it onto the movement plane
+        let direction_to = |position: &Vector3<f32>| -> Vector3<f32> {
+            let direction = Vector3::new(position.x - perception._position.x, 0.0, position.z - perception._position.z);
+            if direction.norm() <= f32::EPSILON { Vector3::zeros() } else { direction.normalize() }
+        };
+
+        let mut command = BehaviorCommand::default();
+        match self._state {
+            BehaviorState::Idle => (),
+            BehaviorState::Patrol => {
+                let patrol_axis = match perception._movement_mode {
+                    MovementMode::SideScroller => Vector3::x(),
+                    MovementMode::Free3D => self._patrol_axis,
+                };
+                let offset_from_home = (perception._position - self._home_position).dot(&patrol_axis);
+                if behavior_data._patrol_distance <= offset_from_home * self._patrol_direction {
+                    self._patrol_direction = -self._patrol_direction;
+                }
+                command._move_direction = patrol_axis * self._patrol_direction;
+            },
+            BehaviorState::Chase => {
+                if let Some(target_position) = perception._target_position.as_ref() {
+                    command._move_direction = direction_to(target_position);
+                    command._dash = 0.0 < behavior_data._dash_range && behavior_data._dash_range < (target_position - perception._position).norm();
+                }
+            },
+            BehaviorState::Attack => {
+                if let Some(target_position) = perception._target_position.as_ref() {
+                    command._face_direction = direction_to(target_position);
+                    if self._attack_cooldown <= 0.0 {
+                        command._attack = true;
+                        self._attack_cooldown = behavior_data._attack_cooldown;
+                    }
+                }
+            },
+            BehaviorState::Flee => {
+                if let Some(target_position) = perception._target_position.as_ref() {
+                    command._move_direction = -direction_to(target_position);
+                }
+            },
+            BehaviorState::ReturnHome => {
+                command._move_direction = direction_to(&self._home_position);
+            },
+        }
+        command
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DELTA_TIME: f32 = 0.1;
+
+    fn create_perception(position: Vector3<f32>, target_position: Option<Vector3<f32>>, hp_ratio: f32) -> BehaviorPerception {
+        BehaviorPerception {
+            _position: position,
+            _target_position: target_position,
+            _hp_ratio: hp_ratio,
+            _movement_mode: MovementMode::SideScroller,
+        }
+    }
+
+    fn create_state_machine(state: BehaviorState) -> BehaviorStateMachine {
+        BehaviorStateMachine::create_behavior_state_machine(state, &Vector3::zeros())
+    }
+
+    #[test]
+    fn test_idle_to_patrol_on_timeout() {
+        let behavior_data = BehaviorData::default();
+        let mut state_machine = create_state_machine(BehaviorState::Idle);
+        let perception = create_perception(Vector3::zeros(), None, 1.0);
+
+        let command = state_machine.update_behavior_state_machine(&behavior_data, &perception, 0.5);
+        assert_eq!(BehaviorState::Idle, state_machine.get_state());
+        assert_eq!(BehaviorCommand::default(), command);
+
+        let command = state_machine.update_behavior_state_machine(&behavior_data, &perception, 0.5);
+        assert_eq!(BehaviorState::Patrol, state_machine.get_state());
+        assert_eq!(Vector3::x(), command._move_direction);
+        assert!(false == command._attack);
+    }
+
+    #[test]
+    fn test_patrol_turns_around_at_patrol_distance() {
+        let behavior_data = BehaviorData::default();
+        let mut state_machine = create_state_machine(BehaviorState::Patrol);
+        let perception = create_perception(Vector3::new(behavior_data._patrol_distance, 0.0, 0.0), None, 1.0);
+        let command = state_machine.update_behavior_state_machine(&behavior_data, &perception, DELTA_TIME);
+        assert_eq!(-Vector3::x(), command._move_direction);
+    }
+
+    #[test]
+    fn test_free_3d_patrol_follows_patrol_axis() {
+        let behavior_data = BehaviorData::default();
+        let mut state_machine = create_state_machine(BehaviorState::Patrol);
+        state_machine.set_patrol_axis(&Vector3::new(0.0, 0.0, -2.0));
+        let mut perception = create_perception(Vector3::zeros(), None, 1.0);
+        perception._movement_mode = MovementMode::Free3D;
+        let command = state_machine.update_behavior_state_machine(&behavior_data, &perception, DELTA_TIME);
+        assert_eq!(Vector3::new(0.0, 0.0, -1.0), command._move_direction);
+
+        perception._position = Vector3::new(0.0, 0.0, -behavior_data._patrol_distance);
+        let command = state_machine.update_behavior_state_machine(&behavior_data, &perception, DELTA_TIME);
+        assert_eq!(Vector3::new(0.0, 0.0, 1.0), command._move_direction);
+    }
+
+    #[test]
+    fn test_patrol_to_chase_on_sight() {
+        let mut behavior_data = BehaviorData::default();
+        let mut state_machine = create_state_machine(BehaviorState::Patrol);
+
+        // out of detection range
+        let perception = create_perception(Vector3::zeros(), Some(Vector3::new(-6.0, 0.0, 0.0)), 1.0);
+        state_machine.update_behavior_state_machine(&behavior_data, &perception, DELTA_TIME);
+        assert_eq!(BehaviorState::Patrol, state_machine.get_state());
+
+        let perception = create_perception(Vector3::zeros(), Some(Vector3::new(-3.0, 0.5, 0.0)), 1.0);
+        let command = state_machine.update_behavior_state_machine(&behavior_data, &perception, DELTA_TIME);
+        assert_eq!(BehaviorState::Chase, state_machine.get_state());
+        assert_eq!(-Vector3::x(), command._move_direction);
+        assert!(false == command._dash);
+
+        behavior_data._dash_range = 2.0;
+        let command = state_machine.update_behavior_state_machine(&behavior_data, &perception, DELTA_TIME);
+        assert!(command._dash);
+    }
+
+    #[test]
+    fn test_chase_to_attack_in_range() {
+        let behavior_data = BehaviorData::default();
+        let mut state_machine = create_state_machine(BehaviorState::Chase);
+        let perception = create_perception(Vector3::zeros(), Some(Vector3::new(1.0, 0.0, 0.0)), 1.0);
+
+        let command = state_machine.update_behavior_state_machine(&behavior_data, &perception, DELTA_TIME);
+        assert_eq!(BehaviorState::Attack, state_machine.get_state());
+        assert_eq!(Vector3::x(), command._face_direction);
+        assert_eq!(Vector3::zeros(), command._move_direction);
+        assert!(command._attack);
+
+        // cooldown
+        let command = state_machine.update_behavior_state_machine(&behavior_data, &perception, DELTA_TIME);
+        assert!(false == command._attack);
+
+        // back to chase when the target steps away
+        let perception = create_perception(Vector3::zeros(), Some(Vector3::new(3.0, 0.0, 0.0)), 1.0);
+        let command = state_machine.update_behavior_state_machine(&behavior_data, &perception, DELTA_TIME);
+        assert_eq!(BehaviorState::Chase, state_machine.get_state());
+        assert_eq!(Vector3::x(), command._move_direction);
+    }
+
+    #[test]
+    fn test_flee_on_low_hp_then_return_home() {
+        let mut behavior_data = BehaviorData::default();
+        behavior_data._flee_hp_ratio = 0.3;
+        let mut state_machine = create_state_machine(BehaviorState::Chase);
+        let position = Vector3::new(2.0, 0.0, 0.0);
+
+        let perception = create_perception(position, Some(Vector3::new(4.0, 0.0, 0.0)), 0.2);
+        let command = state_machine.update_behavior_state_machine(&behavior_data, &perception, DELTA_TIME);
+        assert_eq!(BehaviorState::Flee, state_machine.get_state());
+        assert_eq!(-Vector3::x(), command._move_direction);
+
+        let perception = create_perception(position, None, 0.2);
+        let command = state_machine.update_behavior_state_machine(&behavior_data, &perception, DELTA_TIME);
+        assert_eq!(BehaviorState::ReturnHome, state_machine.get_state());
+        assert_eq!(-Vector3::x(), command._move_direction);
+
+        let perception = create_perception(Vector3::new(0.1, 0.0, 0.0), None, 0.2);
+        let command = state_machine.update_behavior_state_machine(&behavior_data, &perception, DELTA_TIME);
+        assert_eq!(BehaviorState::Idle, state_machine.get_state());
+        assert_eq!(BehaviorCommand::default(), command);
+    }
+
+    #[test]
+    fn test_chase_returns_home_when_leashed_or_target_lost() {
+        let behavior_data = BehaviorData::default();
+        let mut state_machine = create_state_machine(BehaviorState::Chase);
+        let position = Vector3::new(behavior_data._leash_range + 1.0, 0.0, 0.0);
+        let perception = create_perception(position, Some(position + Vector3::new(3.0, 0.0, 0.0)), 1.0);
+        let command = state_machine.update_behavior_state_machine(&behavior_data, &perception, DELTA_TIME);
+        assert_eq!(BehaviorState::ReturnHome, state_machine.get_state());
+        assert_eq!(-Vector3::x(), command._move_direction);
+
+        let mut state_machine = create_state_machine(BehaviorState::Chase);
+        let perception = create_perception(Vector3::zeros(), Some(Vector3::new(0.0, 0.0, 9.0)), 1.0);
+        state_machine.update_behavior_state_machine(&behavior_data, &perception, DELTA_TIME);
+        assert_eq!(BehaviorState::ReturnHome, state_machine.get_state());
+    }
+}
@@ -1,8 +1,9 @@
 use nalgebra::Vector3;
-use rust_engine_3d::scene::render_object::RenderObjectData;
 use rust_engine_3d::utilities::system::RcRefCell;
 use serde::{Deserialize, Serialize};
 
+use crate::game_module::scene_backend::RenderObjectProxy;
+
 
 #[derive(Serialize, Deserialize,Clone, Copy, Debug, PartialEq)]
 pub enum BlockDataType {
@@ -37,7 +38,7 @@ pub struct Block {
     pub _block_name: String,
     pub _block_id: u64,
     pub _block_data: RcRefCell<BlockData>,
-    pub _render_object: RcRefCell<RenderObjectData>,
+    pub _render_object: RcRefCell<dyn RenderObjectProxy>,
     pub _block_properties: Box<BlockProperties>
 }
 
@@ -56,7 +57,7 @@ impl Block {
         block_id: u64,
         block_name: &str,
         block_data: &RcRefCell<BlockData>,
-        render_object: &RcRefCell<RenderObjectData>,
+        render_object: &RcRefCell<dyn RenderObjectProxy>,
         position: &Vector3<f32>,
         rotation: &Vector3<f32>,
         scale: &Vector3<f32>) -> Block {
@@ -81,9 +82,10 @@ impl Block {
     }
 
     pub fn update_transform(&mut self) {
-        let mut render_object = self._render_object.borrow_mut();
-        render_object._transform_object.set_position(&self._block_properties._position);
-        render_object._transform_object.set_rotation(&self._block_properties._rotation);
-        render_object._transform_object.set_scale(&self._block_properties._scale);
+        self._render_object.borrow_mut().set_transform(
+            &self._block_properties._position,
+            &self._block_properties._rotation,
+            &self._block_properties._scale
+        );
     }
 }
\ No newline at end of file
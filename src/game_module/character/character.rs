@@ -1,24 +1,60 @@
+use std::collections::HashMap;
+
 use nalgebra::Vector3;
-use rust_engine_3d::scene::mesh::MeshData;
-use rust_engine_3d::scene::render_object::RenderObjectData;
 use rust_engine_3d::utilities::system::RcRefCell;
 use serde::{Deserialize, Serialize};
-use crate::game_module::character::animation_blend_mask::AnimationBlendMasks;
+use crate::game_module::character::ability::{AbilityData, AbilityType, CharacterAbilities};
+use crate::game_module::character::animation_state_machine::{AnimationGraphData, AnimationLayerStateMachine};
+use crate::game_module::character::attack::{AttackComboStepData, AttackData};
+use crate::game_module::character::behavior_state_machine::{BehaviorData, BehaviorStateMachine};
+use crate::game_module::scene_backend::{AnimationHandle, RenderObjectProxy};
 
-#[derive(Clone, Copy, Debug, PartialEq)]
-pub enum MoveAnimationState {
-    NONE,
-    IDLE,
-    WALK,
-    JUMP,
-}
+pub const ANIMATION_IDLE: &str = "idle";
+pub const ANIMATION_WALK: &str = "walk";
+pub const ANIMATION_RUN: &str = "run";
+pub const ANIMATION_JUMP: &str = "jump";
+pub const ANIMATION_FALL: &str = "fall";
+pub const ANIMATION_LAND: &str = "land";
+pub const ANIMATION_ATTACK: &str = "attack";
+pub const ANIMATION_ATTACK_2: &str = "attack_2";
+pub const ANIMATION_ATTACK_3: &str = "attack_3";
+pub const ANIMATION_HIT: &str = "hit";
+pub const ANIMATION_DEATH: &str = "death";
+pub const ANIMATION_STATE_NONE: &str = "none";
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+// animation notify events
+pub const ANIMATION_EVENT_HIT_WINDOW_START: &str = "hit_window_start";
+pub const ANIMATION_EVENT_HIT_WINDOW_END: &str = "hit_window_end";
+pub const ANIMATION_EVENT_FOOTSTEP: &str = "footstep";
+pub const ANIMATION_EVENT_PLAY_SOUND: &str = "play_sound:";
+pub const ANIMATION_EVENT_PLAY_EFFECT: &str = "play_effect:";
+pub const FOOTSTEP_AUDIO_BANK: &str = "footstep";
+
+// animation to try when a character has no entry for the requested one
+pub const ANIMATION_FALLBACKS: [(&str, &str); 8] = [
+    (ANIMATION_RUN, ANIMATION_WALK),
+    (ANIMATION_FALL, ANIMATION_JUMP),
+    (ANIMATION_LAND, ANIMATION_IDLE),
+    (ANIMATION_ATTACK_3, ANIMATION_ATTACK_2),
+    (ANIMATION_ATTACK_2, ANIMATION_ATTACK),
+    (ANIMATION_WALK, ANIMATION_IDLE),
+    (ANIMATION_HIT, ANIMATION_IDLE),
+    (ANIMATION_DEATH, ANIMATION_IDLE),
+];
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
 pub enum ActionAnimationState {
     NONE,
     ATTACK
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DamageOutcome {
+    None,
+    Hit,
+    Dead,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum SpawnPointType {
     None,
@@ -44,29 +80,74 @@ pub enum CharacterDataType {
 pub struct CharacterData {
     pub _character_type: CharacterDataType,
     pub _model_data_name: String,
-    pub _idle_animation_mesh: String,
-    pub _walk_animation_mesh: String,
-    pub _jump_animation_mesh: String,
-    pub _attack_animation_mesh: String,
+    pub _animation_set: HashMap<String, String>,
+    pub _animation_graph_data_name: String,
+    pub _additive_blend_mask_name: String,
+    pub _dead_despawn_time: f32,
     pub _max_hp: i32,
+    pub _attacks: HashMap<String, AttackData>,
+    pub _attack_combo: Vec<AttackComboStepData>,
+    pub _hit_stun_time: f32,
+    pub _invulnerable_time: f32,
+    pub _knockback_scale: f32,
+    pub _move_speed: f32,
+    pub _jump_speed: f32,
+    pub _gravity_scale: f32,
+    // share of the gap to the input velocity closed in the air per 1 / SIMULATION_TICK_RATE seconds, 1.0 is full control
+    pub _air_control: f32,
+    pub _max_fall_speed: f32,
+    // a jump is still allowed for this long after walking off a ledge
+    pub _coyote_time: f32,
+    // a jump pressed this long before landing is performed on landing
+    pub _jump_buffer_time: f32,
+    // scales the rising speed when the jump button is released early, 1.0 gives a fixed jump height
+    pub _jump_cut_off_scale: f32,
+    // movement abilities of the archetype, the player unlocks locked ones at runtime
+    pub _abilities: HashMap<AbilityType, AbilityData>,
+    // overlapping characters are pushed apart in inverse proportion to their mass
+    pub _mass: f32,
+    pub _behavior: BehaviorData,
 }
 
 pub struct CharacterProperty {
     pub _hp: f32,
+    pub _max_hp: f32,
+}
+
+// side scroller keeps characters on the x axis, free 3d moves them on the xz plane
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum MovementMode {
+    #[default]
+    SideScroller,
+    Free3D,
 }
 
 pub struct CharacterController {
+    pub _prev_position: Vector3<f32>,
     pub _position: Vector3<f32>,
     pub _rotation: Vector3<f32>,
     pub _scale: Vector3<f32>,
     pub _velocity: Vector3<f32>,
     pub _is_ground: bool,
-    pub _is_jump: bool,
-    pub _move_direction: f32
+    pub _is_jump_held: bool,
+    pub _was_jump_held: bool,
+    // a jump that is still rising and can be cut short by releasing the button
+    pub _is_jump_rising: bool,
+    pub _coyote_time: f32,
+    pub _jump_buffer_time: f32,
+    pub _is_dash: bool,
+    pub _dash_time: f32,
+    pub _wall_jump_time: f32,
+    // direction of the wall the character pushed against in the last tick, zero when there is none
+    pub _wall_direction: Vector3<f32>,
+    pub _is_knockback: bool,
+    // horizontal move request of this tick
+    pub _move_direction: Vector3<f32>,
+    pub _movement_mode: MovementMode,
 }
 
 pub struct CharacterBehavior {
-    pub _move_time: f32
+    pub _state_machine: BehaviorStateMachine,
 }
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
@@ -83,15 +164,32 @@ pub struct Character {
     pub _character_id: u64,
     pub _is_player: bool,
     pub _character_data: RcRefCell<CharacterData>,
-    pub _render_object: RcRefCell<RenderObjectData>,
+    pub _render_object: RcRefCell<dyn RenderObjectProxy>,
     pub _character_property: Box<CharacterProperty>,
     pub _controller: Box<CharacterController>,
+    pub _abilities: Box<CharacterAbilities>,
     pub _behavior: Box<CharacterBehavior>,
-    pub _move_animation_state: MoveAnimationState,
+    pub _animation_graph_data: RcRefCell<AnimationGraphData>,
+    pub _base_animation_state: AnimationLayerStateMachine,
+    pub _additive_animation_state: AnimationLayerStateMachine,
     pub _action_animation_state: ActionAnimationState,
-    pub _idle_animation: RcRefCell<MeshData>,
-    pub _walk_animation: RcRefCell<MeshData>,
-    pub _jump_animation: RcRefCell<MeshData>,
-    pub _attack_animation: RcRefCell<MeshData>,
-    pub _animation_blend_masks: *const AnimationBlendMasks
+    pub _is_action_started: bool,
+    pub _animation_events: Vec<String>,
+    pub _attack_name: String,
+    pub _combo_step: usize,
+    pub _is_combo_buffered: bool,
+    pub _is_hit_window: bool,
+    pub _hit_characters: Vec<u64>,
+    pub _hit_stun_time: f32,
+    pub _invulnerable_time: f32,
+    pub _is_hit_started: bool,
+    pub _action_time: f32,
+    pub _prev_action_time: f32,
+    pub _render_interpolation: f32,
+    pub _render_position: Vector3<f32>,
+    pub _spawn_position: Vector3<f32>,
+    pub _is_collision_enabled: bool,
+    pub _dead_time: f32,
+    pub _animations: HashMap<String, AnimationHandle>,
+    pub _additive_blend_mask: HashMap<String, f32>,
 }
\ No newline at end of file
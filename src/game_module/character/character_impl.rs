@@ -1,13 +1,20 @@
+use std::collections::HashMap;
+
 use nalgebra::{Vector3};
 use rust_engine_3d::scene::animation::AnimationPlayArgs;
-use rust_engine_3d::scene::mesh::MeshData;
-use rust_engine_3d::scene::render_object::{AnimationLayer, RenderObjectData};
+use rust_engine_3d::scene::render_object::AnimationLayer;
 use rust_engine_3d::utilities::bounding_box::BoundingBox;
 use rust_engine_3d::utilities::system::{ptr_as_mut, ptr_as_ref, RcRefCell};
-use crate::game_module::character::animation_blend_mask::AnimationBlendMasks;
+use crate::game_module::character::ability::{AbilityType, CharacterAbilities};
+use crate::game_module::character::animation_state_machine::{AnimationGraphData, AnimationLayerData, AnimationLayerStateMachine, AnimationParameters};
+use crate::game_module::character::attack::{AttackComboStepData, AttackData};
+use crate::game_module::character::behavior_state_machine::{BehaviorData, BehaviorPerception, BehaviorStateMachine};
 
 use crate::game_module::character::character::*;
+use crate::game_module::collision::{sweep_bound_box_axis, COLLISION_QUERY_MARGIN, GROUND_PROBE_DISTANCE};
 use crate::game_module::game_constants::*;
+use crate::game_module::game_resource::DEFAULT_GAME_DATA_NAME;
+use crate::game_module::scene_backend::{AnimationHandle, RenderObjectProxy};
 
 
 impl Default for CharacterData {
@@ -15,19 +22,61 @@ impl Default for CharacterData {
         CharacterData {
             _character_type: CharacterDataType::UrsusArctos,
             _model_data_name: String::default(),
-            _idle_animation_mesh: String::default(),
-            _walk_animation_mesh: String::default(),
-            _jump_animation_mesh: String::default(),
-            _attack_animation_mesh: String::default(),
+            _animation_set: HashMap::new(),
+            _animation_graph_data_name: String::from(DEFAULT_GAME_DATA_NAME),
+            _additive_blend_mask_name: String::default(),
+            _dead_despawn_time: 3.0,
             _max_hp: 100,
+            _attacks: HashMap::from([(String::from(ANIMATION_ATTACK), AttackData::default())]),
+            _attack_combo: vec![AttackComboStepData::default()],
+            _hit_stun_time: 0.4,
+            _invulnerable_time: 0.6,
+            _knockback_scale: 1.0,
+            _move_speed: PLAYER_MOVE_SPEED,
+            _jump_speed: PLAYER_JUMP_SPEED,
+            _gravity_scale: PLAYER_GRAVITY_SCALE,
+            _air_control: PLAYER_AIR_CONTROL,
+            _max_fall_speed: PLAYER_MAX_FALL_SPEED,
+            _abilities: HashMap::new(),
+            _coyote_time: PLAYER_COYOTE_TIME,
+            _jump_buffer_time: PLAYER_JUMP_BUFFER_TIME,
+            _jump_cut_off_scale: PLAYER_JUMP_CUT_OFF_SCALE,
+            _mass: PLAYER_MASS,
+            _behavior: BehaviorData::default(),
         }
     }
 }
 
 impl CharacterProperty {
-    pub fn create_character_property() -> CharacterProperty {
+    pub fn create_character_property(max_hp: i32) -> CharacterProperty {
         CharacterProperty {
-            _hp: 0.0,
+            _hp: max_hp as f32,
+            _max_hp: max_hp as f32,
+        }
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self._hp <= 0.0
+    }
+
+    // 0.0 ~ 1.0, a character without max hp counts as unhurt
+    pub fn get_hp_ratio(&self) -> f32 {
+        if self._max_hp <= 0.0 {
+            return 1.0;
+        }
+        0f32.max(1f32.min(self._hp / self._max_hp))
+    }
+
+    pub fn apply_damage(&mut self, damage: f32) -> DamageOutcome {
+        if self.is_dead() {
+            return DamageOutcome::None;
+        }
+
+        self._hp = 0f32.max(self._hp - damage);
+        if self.is_dead() {
+            DamageOutcome::Dead
+        } else {
+            DamageOutcome::Hit
         }
     }
 }
@@ -35,107 +84,399 @@ impl CharacterProperty {
 impl CharacterController {
     pub fn create_character_controller() -> CharacterController {
         CharacterController {
+            _prev_position: Vector3::zeros(),
             _position: Vector3::zeros(),
             _rotation: Vector3::zeros(),
             _scale: Vector3::new(1.0, 1.0, 1.0),
             _velocity: Vector3::zeros(),
-            _is_jump: false,
             _is_ground: false,
-            _move_direction: 0.0
+            _is_jump_held: false,
+            _was_jump_held: false,
+            _is_jump_rising: false,
+            _coyote_time: 0.0,
+            _jump_buffer_time: 0.0,
+            _is_dash: false,
+            _dash_time: 0.0,
+            _wall_jump_time: 0.0,
+            _wall_direction: Vector3::zeros(),
+            _is_knockback: false,
+            _move_direction: Vector3::zeros(),
+            _movement_mode: MovementMode::SideScroller,
         }
     }
 
     pub fn initialize(&mut self) {
+        self._prev_position = Vector3::zeros();
         self._position = Vector3::zeros();
         self._rotation = Vector3::zeros();
         self._scale = Vector3::new(1.0, 1.0, 1.0);
         self._velocity = Vector3::zeros();
         self._is_ground = true;
-        self._is_jump = false;
-        self._move_direction = 0.0;
+        self._is_knockback = false;
+        self._move_direction = Vector3::zeros();
+        self._is_jump_held = false;
+        self._was_jump_held = false;
+        self.clear_jump();
     }
 
     pub fn is_stop(&self) -> bool {
-        self._velocity.x == 0.0 && self._velocity.y == 0.0
+        self._velocity.x == 0.0 && self._velocity.y == 0.0 && self._velocity.z == 0.0
+    }
+
+    pub fn set_movement_mode(&mut self, movement_mode: MovementMode) {
+        self._movement_mode = movement_mode;
     }
 
     pub fn set_move_walk(&mut self, is_left: bool) {
-        self._move_direction = if is_left { -1.0 } else { 1.0 };
+        self._move_direction = Vector3::new(if is_left { -1.0 } else { 1.0 }, 0.0, 0.0);
     }
 
-    pub fn set_move_jump(&mut self) {
-        if self._is_ground {
-            self._is_jump = true;
+    // the side scroller only keeps the sign of x
+    pub fn set_move_direction(&mut self, move_direction: &Vector3<f32>) {
+        let mut move_direction = Vector3::new(move_direction.x, 0.0, move_direction.z);
+        if MovementMode::SideScroller == self._movement_mode {
+            let direction = if 0.0 != move_direction.x { move_direction.x.signum() } else { 0.0 };
+            move_direction = Vector3::new(direction, 0.0, 0.0);
+        } else if 1.0 < move_direction.norm() {
+            move_direction = move_direction.normalize();
         }
+        self._move_direction = move_direction;
+    }
+
+    // called while the jump button is held, the press starts a jump and the release cuts it short
+    pub fn set_move_jump(&mut self) {
+        self._is_jump_held = true;
+    }
+
+    pub fn set_move_dash(&mut self) {
+        self._is_dash = true;
+    }
+
+    pub fn is_dashing(&self) -> bool {
+        0.0 < self._dash_time
+    }
+
+    pub fn clear_jump(&mut self) {
+        self._is_jump_rising = false;
+        self._coyote_time = 0.0;
+        self._jump_buffer_time = 0.0;
+        self._dash_time = 0.0;
+        self._wall_jump_time = 0.0;
     }
 
     pub fn get_direction(&self) -> f32 {
         if self._rotation.y.is_sign_positive() { -1.0 } else { 1.0 }
     }
 
+    pub fn get_forward(&self) -> Vector3<f32> {
+        Vector3::new(-self._rotation.y.sin(), 0.0, -self._rotation.y.cos())
+    }
+
     pub fn set_direction(&mut self, direction: f32) {
         self._rotation.y = direction * std::f32::consts::PI * -0.5;
     }
 
+    // faces a horizontal direction, the side scroller only faces left or right
+    pub fn set_forward(&mut self, forward: &Vector3<f32>) {
+        if MovementMode::SideScroller == self._movement_mode {
+            if 0.0 != forward.x {
+                self.set_direction(forward.x.signum());
+            }
+        } else if 0.0 != forward.x || 0.0 != forward.z {
+            self._rotation.y = (-forward.x).atan2(-forward.z);
+        }
+    }
+
     pub fn set_on_ground(&mut self, ground_height: f32) {
         self._position.y = ground_height;
         self._is_ground = true;
         self._velocity.y = 0.0;
     }
 
-    pub fn update_character_controller(&mut self, _actor_bound_box: &BoundingBox, blocks: &Vec<*const RenderObjectData>, delta_time: f32) {
-        let prev_position = self._position.clone_owned();
+    pub fn set_position(&mut self, position: &Vector3<f32>) {
+        self._position.clone_from(position);
+        self._prev_position.clone_from(position);
+        self._velocity = Vector3::zeros();
+        self._is_ground = false;
+        self._is_knockback = false;
+        self.clear_jump();
+    }
 
-        // move on ground
-        if 0.0 != self._move_direction {
-            self._velocity.x = self._move_direction * PLAYER_MOVE_SPEED;
-            self._position.x += self._velocity.x * delta_time;
-            self._rotation.y = self._move_direction * std::f32::consts::PI * -0.5;
-        } else {
-            self._velocity.x = 0.0;
+    // moves along a single axis without passing through the colliders
+    pub fn push(&mut self, local_bound_box: &BoundingBox, axis: usize, push_delta: f32, blocks: &[*const dyn RenderObjectProxy]) {
+        let colliders: Vec<&BoundingBox> = blocks.iter().map(|block| ptr_as_ref(*block).get_bound_box()).collect();
+        let (delta, _is_hit) = sweep_bound_box_axis(
+            &(self._position + local_bound_box._min),
+            &(self._position + local_bound_box._max),
+            axis,
+            push_delta,
+            &colliders
+        );
+        self._position[axis] += delta;
+    }
+
+    pub fn apply_impulse(&mut self, impulse: &Vector3<f32>) {
+        self._velocity.clone_from(impulse);
+        self._is_knockback = true;
+        self.clear_jump();
+        if 0.0 < impulse.y {
+            self._is_ground = false;
         }
+    }
+
+    pub fn reset_input(&mut self) {
+        self._move_direction = Vector3::zeros();
+        self._is_jump_held = false;
+        self._is_dash = false;
+    }
 
-        if self._is_jump {
-            self._velocity.y = PLAYER_JUMP_SPEED;
+    // a character falling onto a collider within the jump buffer time keeps an air press for the landing
+    pub fn is_landing_expected(&self, character_data: &CharacterData, local_bound_box: &BoundingBox, colliders: &[&BoundingBox]) -> bool {
+        if self._is_ground {
+            return false;
+        }
+        let buffer_time = character_data._jump_buffer_time;
+        let fall_delta = self._velocity.y * buffer_time - 0.5 * GRAVITY * character_data._gravity_scale * buffer_time * buffer_time;
+        let fall_delta = fall_delta.max(-character_data._max_fall_speed * buffer_time);
+        if 0.0 <= fall_delta {
+            return false;
+        }
+        let (_delta, is_hit) = sweep_bound_box_axis(
+            &(self._position + local_bound_box._min),
+            &(self._position + local_bound_box._max),
+            1,
+            fall_delta,
+            colliders
+        );
+        is_hit
+    }
+
+    pub fn update_jump(&mut self, character_data: &CharacterData, abilities: &mut CharacterAbilities, is_landing_expected: bool, delta_time: f32) {
+        let is_jump_pressed = self._is_jump_held && false == self._was_jump_held;
+        self._was_jump_held = self._is_jump_held;
+
+        // timers set in this tick start counting down in the next tick
+        self._coyote_time = 0f32.max(self._coyote_time - delta_time);
+        self._jump_buffer_time = 0f32.max(self._jump_buffer_time - delta_time);
+        if self._is_ground {
+            self._coyote_time = character_data._coyote_time;
+        }
+        if is_jump_pressed {
+            self._jump_buffer_time = character_data._jump_buffer_time;
+        }
+
+        let can_jump = self._is_ground || 0.0 < self._coyote_time;
+        if can_jump && 0.0 < self._jump_buffer_time {
+            self._velocity.y = character_data._jump_speed;
             self._is_ground = false;
+            self._is_jump_rising = true;
+            self._coyote_time = 0.0;
+            self._jump_buffer_time = 0.0;
+        } else if is_jump_pressed && false == self._is_ground {
+            self.update_air_jump(character_data, abilities, is_landing_expected);
+        }
+
+        if self._is_jump_rising {
+            if self._velocity.y <= 0.0 {
+                self._is_jump_rising = false;
+            } else if false == self._is_jump_held {
+                self._velocity.y *= character_data._jump_cut_off_scale.clamp(0.0, 1.0);
+                self._is_jump_rising = false;
+            }
+        }
+    }
+
+    // wall jump when pressing against a wall, otherwise double jump unless the press is buffered for the landing
+    pub fn update_air_jump(&mut self, character_data: &CharacterData, abilities: &mut CharacterAbilities, is_landing_expected: bool) {
+        if self.is_on_wall() {
+            if let Some(ability_data) = abilities.use_ability(AbilityType::WallJump, &character_data._abilities) {
+                let push_direction = -self._wall_direction.normalize();
+                self._velocity.x = push_direction.x * ability_data._push_speed;
+                self._velocity.y = ability_data._speed;
+                self._velocity.z = push_direction.z * ability_data._push_speed;
+                self._wall_jump_time = ability_data._duration;
+                self._is_jump_rising = true;
+                self._jump_buffer_time = 0.0;
+                self.set_forward(&push_direction);
+                self._wall_direction = Vector3::zeros();
+                return;
+            }
         }
 
-        // fall
-        self._velocity.y -= GRAVITY * delta_time;
-        self._position.y += self._velocity.y * delta_time;
-        if self._position.y <= GROUND_HEIGHT {
-            self.set_on_ground(GROUND_HEIGHT);
+        if is_landing_expected {
+            return;
         }
 
-        for block in blocks.iter() {
-            let block_bound_box = &ptr_as_ref(*block)._bound_box;
-            if block_bound_box.collide_bound_box(&self._position) {
-                if block_bound_box.collide_bound_box_x(&prev_position) && block_bound_box._max.y <= prev_position.y {
-                    self.set_on_ground(block_bound_box._max.y)
-                } else if block_bound_box.collide_bound_box_y(&prev_position) && false == block_bound_box.collide_bound_box_x(&prev_position) {
-                    self._position.x = prev_position.x;
+        if let Some(ability_data) = abilities.use_ability(AbilityType::DoubleJump, &character_data._abilities) {
+            self._velocity.y = ability_data._speed;
+            self._is_jump_rising = true;
+            self._jump_buffer_time = 0.0;
+        }
+    }
+
+    pub fn update_dash(&mut self, character_data: &CharacterData, abilities: &mut CharacterAbilities, delta_time: f32) {
+        if self._is_dash && false == self._is_knockback && false == self.is_dashing() {
+            if let Some(ability_data) = abilities.use_ability(AbilityType::Dash, &character_data._abilities) {
+                let forward = self.get_forward();
+                self._velocity.x = forward.x * ability_data._speed;
+                self._velocity.z = forward.z * ability_data._speed;
+                self._dash_time = ability_data._duration;
+                self._is_jump_rising = false;
+                return;
+            }
+        }
+
+        if self.is_dashing() {
+            self._dash_time = 0f32.max(self._dash_time - delta_time);
+        }
+    }
+
+    pub fn is_on_wall(&self) -> bool {
+        0.0 != self._wall_direction.x || 0.0 != self._wall_direction.z
+    }
+
+    // local_bound_box is the actor's collision box relative to _position
+    pub fn update_character_controller(&mut self, character_data: &CharacterData, abilities: &mut CharacterAbilities, local_bound_box: &BoundingBox, blocks: &Vec<*const dyn RenderObjectProxy>, delta_time: f32) {
+        let prev_position = self._position.clone_owned();
+        self._prev_position.clone_from(&prev_position);
+        abilities.update_abilities(&character_data._abilities, self._is_ground, delta_time);
+
+        // move, with partial control while in the air
+        let move_velocity = self._move_direction * character_data._move_speed;
+        if Vector3::zeros() != self._move_direction && false == self.is_dashing() && self._wall_jump_time <= 0.0 {
+            self.set_forward(&move_velocity);
+        }
+        self.update_dash(character_data, abilities, delta_time);
+        if self.is_dashing() {
+            // keeps the dash velocity
+        } else if 0.0 < self._wall_jump_time {
+            self._wall_jump_time = 0f32.max(self._wall_jump_time - delta_time);
+        } else if self._is_knockback {
+            let horizontal_speed = self._velocity.x.hypot(self._velocity.z);
+            if self._is_ground && 0.0 < horizontal_speed {
+                let friction = horizontal_speed.min(KNOCKBACK_GROUND_FRICTION * delta_time) / horizontal_speed;
+                self._velocity.x -= self._velocity.x * friction;
+                self._velocity.z -= self._velocity.z * friction;
+            }
+        } else if self._is_ground {
+            self._velocity.x = move_velocity.x;
+            self._velocity.z = move_velocity.z;
+        } else {
+            // the same air control at any tick rate
+            let air_control = 1.0 - (1.0 - character_data._air_control.clamp(0.0, 1.0)).powf(delta_time * SIMULATION_TICK_RATE as f32);
+            self._velocity.x += (move_velocity.x - self._velocity.x) * air_control;
+            self._velocity.z += (move_velocity.z - self._velocity.z) * air_control;
+        }
+
+        if MovementMode::SideScroller == self._movement_mode {
+            self._velocity.z = 0.0;
+        }
+
+        let colliders: Vec<&BoundingBox> = blocks.iter().map(|block| ptr_as_ref(*block).get_bound_box()).collect();
+        let is_landing_expected = self.is_landing_expected(character_data, local_bound_box, &colliders);
+        self.update_jump(character_data, abilities, is_landing_expected, delta_time);
+
+        // fall, dashes keep their height and walls slow down the fall
+        if self.is_dashing() {
+            self._velocity.y = 0.0;
+        } else {
+            self._velocity.y -= GRAVITY * character_data._gravity_scale * delta_time;
+            let mut max_fall_speed = character_data._max_fall_speed;
+            if self.is_on_wall() && abilities.is_unlocked(AbilityType::WallJump) {
+                if let Some(ability_data) = character_data._abilities.get(&AbilityType::WallJump) {
+                    if 0.0 < ability_data._slide_speed {
+                        max_fall_speed = max_fall_speed.min(ability_data._slide_speed);
+                    }
                 }
             }
+            self._velocity.y = self._velocity.y.max(-max_fall_speed);
+        }
+
+        // swept box against the blocks, horizontal passes first and then the vertical pass
+        let move_delta = self._velocity * delta_time;
+        self._wall_direction = Vector3::zeros();
+        for axis in [0, 2] {
+            let (delta, is_hit) = sweep_bound_box_axis(
+                &(self._position + local_bound_box._min),
+                &(self._position + local_bound_box._max),
+                axis,
+                move_delta[axis],
+                &colliders
+            );
+            self._position[axis] += delta;
+            if is_hit {
+                self._velocity[axis] = 0.0;
+                self._dash_time = 0.0;
+                self._wall_direction[axis] = move_delta[axis].signum();
+            }
         }
 
-        // reset
-        self._is_jump = false;
-        self._move_direction = 0.0;
+        if 0.0 == move_delta.y {
+            // dashes keep their height, a ground dash stays on the ground while there is ground below it
+            let (_delta, is_supported) = sweep_bound_box_axis(
+                &(self._position + local_bound_box._min),
+                &(self._position + local_bound_box._max),
+                1,
+                -GROUND_PROBE_DISTANCE,
+                &colliders
+            );
+            self._is_ground = self._is_ground && is_supported;
+            return;
+        }
+
+        let (delta, is_hit) = sweep_bound_box_axis(
+            &(self._position + local_bound_box._min),
+            &(self._position + local_bound_box._max),
+            1,
+            move_delta.y,
+            &colliders
+        );
+        self._position.y += delta;
+        self._is_ground = false;
+        if is_hit {
+            if move_delta.y < 0.0 {
+                self.set_on_ground(self._position.y);
+            } else {
+                // hit a ceiling
+                self._velocity.y = 0.0;
+            }
+        }
     }
 }
 
 impl CharacterBehavior {
-    pub fn create_character_behavior() -> CharacterBehavior {
+    pub fn create_character_behavior(behavior_data: &BehaviorData, home_position: &Vector3<f32>) -> CharacterBehavior {
         CharacterBehavior {
-            _move_time: 0.0,
+            _state_machine: BehaviorStateMachine::create_behavior_state_machine(behavior_data._initial_state, home_position),
         }
     }
 
-    pub fn update_behavior(&mut self, character: &mut Character, delta_time: f32) {
-        character.set_move_walk(self._move_time < 2.0);
-        self._move_time += delta_time;
-        if 4.0 <= self._move_time {
-            self._move_time = 0.0;
+    pub fn update_behavior(&mut self, character: &mut Character, player_position: Option<&Vector3<f32>>, delta_time: f32) {
+        let perception = BehaviorPerception {
+            _position: character.get_position().clone_owned(),
+            _target_position: player_position.cloned(),
+            _hp_ratio: character._character_property.get_hp_ratio(),
+            _movement_mode: character._controller._movement_mode,
+        };
+        let command = {
+            let character_data = character._character_data.borrow();
+            self._state_machine.update_behavior_state_machine(&character_data._behavior, &perception, delta_time)
+        };
+
+        if Vector3::zeros() != command._move_direction {
+            character.set_move_direction(&command._move_direction);
+        }
+
+        if Vector3::zeros() != command._face_direction {
+            character._controller.set_forward(&command._face_direction);
+        }
+
+        if command._attack {
+            character.set_action_attack();
+        }
+
+        if command._dash {
+            character.set_move_dash();
         }
     }
 }
@@ -147,12 +488,10 @@ impl Character {
         is_player: bool,
         character_name: &str,
         character_data: &RcRefCell<CharacterData>,
-        render_object: &RcRefCell<RenderObjectData>,
-        idle_animation: &RcRefCell<MeshData>,
-        walk_animation: &RcRefCell<MeshData>,
-        jump_animation: &RcRefCell<MeshData>,
-        attack_animation: &RcRefCell<MeshData>,
-        animation_blend_masks: *const AnimationBlendMasks,
+        render_object: &RcRefCell<dyn RenderObjectProxy>,
+        animations: HashMap<String, AnimationHandle>,
+        animation_graph_data: &RcRefCell<AnimationGraphData>,
+        additive_blend_mask: HashMap<String, f32>,
         position: &Vector3<f32>,
         rotation: &Vector3<f32>,
         scale: &Vector3<f32>
@@ -163,85 +502,148 @@ impl Character {
             _character_name: String::from(character_name),
             _character_data: character_data.clone(),
             _render_object: render_object.clone(),
-            _character_property: Box::new(CharacterProperty::create_character_property()),
+            _character_property: Box::new(CharacterProperty::create_character_property(character_data.borrow()._max_hp)),
             _controller: Box::new(CharacterController::create_character_controller()),
-            _behavior: Box::new(CharacterBehavior::create_character_behavior()),
-            _move_animation_state: MoveAnimationState::NONE,
+            _abilities: Box::new(CharacterAbilities::create_character_abilities(&character_data.borrow()._abilities)),
+            _behavior: Box::new(CharacterBehavior::create_character_behavior(&character_data.borrow()._behavior, position)),
+            _animation_graph_data: animation_graph_data.clone(),
+            _base_animation_state: AnimationLayerStateMachine::create_animation_layer_state_machine(&animation_graph_data.borrow()._base_layer),
+            _additive_animation_state: AnimationLayerStateMachine::create_animation_layer_state_machine(&animation_graph_data.borrow()._additive_layer),
             _action_animation_state: ActionAnimationState::NONE,
-            _idle_animation: idle_animation.clone(),
-            _walk_animation: walk_animation.clone(),
-            _jump_animation: jump_animation.clone(),
-            _attack_animation: attack_animation.clone(),
-            _animation_blend_masks: animation_blend_masks
+            _is_action_started: false,
+            _animation_events: Vec::new(),
+            _attack_name: String::from(ANIMATION_ATTACK),
+            _combo_step: 0,
+            _is_combo_buffered: false,
+            _is_hit_window: false,
+            _hit_characters: Vec::new(),
+            _hit_stun_time: 0.0,
+            _invulnerable_time: 0.0,
+            _is_hit_started: false,
+            _action_time: 0.0,
+            _prev_action_time: 0.0,
+            _render_interpolation: 1.0,
+            _render_position: position.clone_owned(),
+            _spawn_position: position.clone_owned(),
+            _is_collision_enabled: true,
+            _dead_time: 0.0,
+            _animations: animations,
+            _additive_blend_mask: additive_blend_mask,
         };
         character._controller._position.clone_from(position);
+        character._controller._prev_position.clone_from(position);
         character._controller._rotation.clone_from(rotation);
         character._controller._scale.clone_from(scale);
+        character._behavior._state_machine.set_patrol_axis(&character._controller.get_forward());
+        character.update_transform(1.0);
+        character.play_animation_state(
+            &animation_graph_data.borrow()._base_layer,
+            character._base_animation_state.get_state(),
+            0.0,
+            AnimationLayer::BaseLayer
+        );
         character
     }
     pub fn get_character_id(&self) -> u64 { self._character_id }
 
-    pub fn set_move_animation(&mut self, move_animation_state: MoveAnimationState) {
-        let mut animation_info = AnimationPlayArgs::default();
-        let mut render_object = self._render_object.borrow_mut();
-        match move_animation_state {
-            MoveAnimationState::IDLE => {
-                render_object.set_animation(&self._idle_animation, &animation_info, AnimationLayer::BaseLayer);
-            },
-            MoveAnimationState::WALK => {
-                render_object.set_animation(&self._walk_animation, &animation_info, AnimationLayer::BaseLayer);
-            },
-            MoveAnimationState::JUMP => {
-                animation_info._animation_loop = false;
-                render_object.set_animation(&self._jump_animation, &animation_info, AnimationLayer::BaseLayer);
-            },
-            _ => ()
+    pub fn get_animation(&self, animation_name: &str) -> Option<&AnimationHandle> {
+        let mut animation_name = animation_name;
+        loop {
+            if let Some(animation) = self._animations.get(animation_name) {
+                return Some(animation);
+            }
+
+            match ANIMATION_FALLBACKS.iter().find(|(name, _)| *name == animation_name) {
+                Some((_, fallback_animation_name)) => animation_name = fallback_animation_name,
+                None => return None,
+            }
+        }
+    }
+
+    pub fn play_animation(&self, animation_name: &str, animation_play_args: &AnimationPlayArgs, layer: AnimationLayer) {
+        if let Some(animation) = self.get_animation(animation_name) {
+            self._render_object.borrow_mut().play_animation(animation, animation_play_args, layer);
+        }
+    }
+
+    pub fn play_animation_state(&self, layer_data: &AnimationLayerData, state: &str, blend_time: f32, layer: AnimationLayer) {
+        if let Some(state_data) = layer_data._states.get(state) {
+            let mut animation_info = AnimationPlayArgs::default();
+            animation_info._animation_speed = state_data._speed;
+            animation_info._animation_loop = state_data._loop;
+            animation_info._animation_blend_time = blend_time;
+            animation_info._animation_fade_out_time = state_data._fade_out_time;
+            animation_info._force_animation_setting = state_data._force_animation_setting;
+            if state_data._use_attack_animation {
+                self.play_animation(&self._attack_name, &animation_info, layer);
+            } else {
+                self.play_animation(&state_data._animation, &animation_info, layer);
+            }
         }
-        self._move_animation_state = move_animation_state;
-        self.update_animation_blend_masks();
     }
 
     pub fn set_action_animation(&mut self, action_animation_state: ActionAnimationState) {
-        let mut animation_info = AnimationPlayArgs::default();
-        let mut render_object = self._render_object.borrow_mut();
-        let additive_animation_play_info = render_object.get_animation_play_info(AnimationLayer::AdditiveLayer);
-        match action_animation_state {
-            ActionAnimationState::ATTACK => {
-                if self._action_animation_state == ActionAnimationState::NONE || CONTINUOUS_ATTACK_TIME < additive_animation_play_info._animation_play_time {
-                    animation_info._animation_loop = false;
-                    animation_info._force_animation_setting = true;
-                    animation_info._animation_fade_out_time = 0.1;
-                    render_object.set_animation(&self._attack_animation, &animation_info, AnimationLayer::AdditiveLayer);
-                }
-            },
-            _ => ()
+        if ActionAnimationState::NONE == action_animation_state {
+            self._is_hit_window = false;
+            self._is_combo_buffered = false;
         }
         self._action_animation_state = action_animation_state;
-        self.update_animation_blend_masks();
     }
 
-    pub fn is_move_state(&self, move_state: MoveAnimationState) -> bool {
-        move_state == self._move_animation_state
+    pub fn get_combo_step_data(&self) -> Option<&AttackComboStepData> {
+        ptr_as_ref(self._character_data.as_ptr())._attack_combo.get(self._combo_step)
+    }
+
+    pub fn has_next_combo_step(&self) -> bool {
+        self._combo_step + 1 < self._character_data.borrow()._attack_combo.len()
+    }
+
+    pub fn start_combo_step(&mut self, combo_step: usize) {
+        self._combo_step = combo_step;
+        if let Some(combo_step_data) = self.get_combo_step_data() {
+            self._attack_name = combo_step_data._attack_name.clone();
+        }
+        self._action_time = 0.0;
+        self._prev_action_time = 0.0;
+        self._is_action_started = true;
+        self._is_combo_buffered = false;
+        self._is_hit_window = false;
+        self._hit_characters.clear();
+        self.set_action_animation(ActionAnimationState::ATTACK);
     }
 
-    pub fn set_move_idle(&mut self) {
-        self.set_move_animation(MoveAnimationState::IDLE);
+    pub fn can_control(&self) -> bool {
+        false == self.is_dead() && false == self.is_hit_stun()
     }
 
     pub fn set_move_walk(&mut self, is_left: bool) {
-        self._controller.set_move_walk(is_left);
-        if false == self.is_move_state(MoveAnimationState::WALK) && self._controller._is_ground {
-            self.set_move_animation(MoveAnimationState::WALK);
+        if self.can_control() {
+            self._controller.set_move_walk(is_left);
+        }
+    }
+
+    pub fn set_move_direction(&mut self, move_direction: &Vector3<f32>) {
+        if self.can_control() {
+            self._controller.set_move_direction(move_direction);
         }
     }
 
     pub fn set_move_jump(&mut self) {
-        if self._controller._is_ground {
+        if self.can_control() {
             self._controller.set_move_jump();
-            self.set_move_animation(MoveAnimationState::JUMP);
         }
     }
 
+    pub fn set_move_dash(&mut self) {
+        if self.can_control() {
+            self._controller.set_move_dash();
+        }
+    }
+
+    pub fn unlock_ability(&mut self, ability_type: AbilityType) -> bool {
+        self._abilities.set_unlocked(ability_type, true)
+    }
+
     pub fn is_action(&self, action: ActionAnimationState) -> bool {
         action == self._action_animation_state
     }
@@ -251,73 +653,597 @@ impl Character {
     }
 
     pub fn set_action_attack(&mut self) {
-        self.set_action_animation(ActionAnimationState::ATTACK);
-    }
+        if false == self.can_control() {
+            return;
+        }
 
-    pub fn is_attacking(&self) -> bool {
         if self.is_action(ActionAnimationState::ATTACK) {
-            let animation_play_infos = &ptr_as_ref(self._render_object.as_ptr())._animation_play_infos;
-            let animation_play_info = &animation_play_infos[AnimationLayer::AdditiveLayer as usize];
-            let attack_time: f32 = 0.15;
-            return animation_play_info._prev_animation_play_time < attack_time && attack_time <= animation_play_info._animation_play_time;
+            if let Some(combo_step_data) = self.get_combo_step_data() {
+                if combo_step_data._combo_end_time <= self._action_time {
+                    self.start_combo_step(0);
+                } else if combo_step_data._buffer_start_time <= self._action_time && self.has_next_combo_step() {
+                    self._is_combo_buffered = true;
+                }
+            }
+        } else {
+            self.start_combo_step(0);
         }
-        false
     }
 
-    pub fn get_attack_point(&self) -> Vector3<f32> {
-        self._controller._position + Vector3::new(self._controller.get_direction(), 1.0, 0.0)
+    pub fn set_dead(&mut self) {
+        self._is_collision_enabled = false;
+        self._dead_time = 0.0;
+        self._controller.reset_input();
+        self.set_action_idle();
+    }
+
+    pub fn set_fall_dead(&mut self) {
+        self._character_property._hp = 0.0;
+        self.set_dead();
+    }
+
+    pub fn respawn(&mut self) {
+        let spawn_position = self._spawn_position.clone_owned();
+        self._controller.set_position(&spawn_position);
+        self._hit_stun_time = 0.0;
+        self.set_action_idle();
+    }
+
+    // knocks the character away from the attacker, stuns it and makes it briefly invulnerable
+    pub fn set_hit(&mut self, attacker_position: &Vector3<f32>, attacker_forward: &Vector3<f32>, knockback: &Vector3<f32>) {
+        let character_data = ptr_as_ref(self._character_data.as_ptr());
+        let mut forward = self.get_position() - attacker_position;
+        forward.y = 0.0;
+        if forward.norm() <= f32::EPSILON {
+            forward.clone_from(attacker_forward);
+        }
+        let forward = forward.normalize();
+        let side = Vector3::y().cross(&forward);
+        let impulse = (forward * knockback.x + Vector3::y() * knockback.y + side * knockback.z) * character_data._knockback_scale;
+
+        self._controller.reset_input();
+        self._controller.apply_impulse(&impulse);
+        self._hit_stun_time = character_data._hit_stun_time;
+        self._invulnerable_time = character_data._invulnerable_time;
+        self._is_hit_started = true;
+        self.set_action_idle();
+    }
+
+    pub fn is_hit_stun(&self) -> bool {
+        0.0 < self._hit_stun_time
+    }
+
+    pub fn is_invulnerable(&self) -> bool {
+        0.0 < self._invulnerable_time
+    }
+
+    pub fn can_despawn(&self) -> bool {
+        self.is_dead() && self._character_data.borrow()._dead_despawn_time <= self._dead_time
+    }
+
+    pub fn get_attack_data(&self) -> Option<&AttackData> {
+        ptr_as_ref(self._character_data.as_ptr())._attacks.get(&self._attack_name)
+    }
+
+    pub fn is_hit_window(&self) -> bool {
+        if false == self.is_action(ActionAnimationState::ATTACK) {
+            return false;
+        }
+
+        match self.get_attack_data() {
+            Some(attack_data) => attack_data.is_active_time(self._action_time).unwrap_or(self._is_hit_window),
+            None => false,
+        }
+    }
+
+    pub fn collide_attack(&self, target: &Character) -> bool {
+        match self.get_attack_data() {
+            Some(attack_data) => {
+                // the simulated box, the render bounds lag behind by the interpolation
+                let (target_min, target_max) = target.get_collision_bound_box();
+                let target_bound_box = BoundingBox::create_bounding_box(&target_min, &target_max);
+                attack_data.collide_attack(self.get_position(), &self._controller.get_forward(), &target_bound_box)
+            },
+            None => false,
+        }
+    }
+
+    pub fn is_hit_character(&self, character_id: u64) -> bool {
+        self._hit_characters.contains(&character_id)
+    }
+
+    pub fn add_hit_character(&mut self, character_id: u64) {
+        self._hit_characters.push(character_id);
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self._character_property.is_dead()
     }
 
     pub fn get_position(&self) -> &Vector3<f32> {
         &self._controller._position
     }
 
-    pub fn collide_bound_box(&self, pos: &Vector3<f32>) -> bool {
-        self._render_object.borrow()._bound_box.collide_in_radius(pos)
+    pub fn get_bound_box_center(&self) -> Vector3<f32> {
+        self._render_object.borrow().get_bound_box()._center.clone_owned()
+    }
+
+    // world space collision box at the simulated position, returns min and max
+    pub fn get_collision_bound_box(&self) -> (Vector3<f32>, Vector3<f32>) {
+        let local_bound_box = self.get_local_bound_box();
+        (self.get_position() + local_bound_box._min, self.get_position() + local_bound_box._max)
     }
 
-    pub fn update_transform(&mut self) {
-        let mut render_object = self._render_object.borrow_mut();
-        render_object._transform_object.set_position(&self._controller._position);
-        render_object._transform_object.set_rotation(&self._controller._rotation);
-        render_object._transform_object.set_scale(&self._controller._scale);
+    // region the controller can reach within one tick, colliders outside of it are skipped
+    pub fn get_collision_query_region(&self, delta_time: f32) -> (Vector3<f32>, Vector3<f32>) {
+        let character_data = self._character_data.borrow();
+        let local_bound_box = self.get_local_bound_box();
+        let max_speed = self._controller._velocity.norm() + character_data._move_speed + character_data._jump_speed + GRAVITY * character_data._gravity_scale * delta_time;
+        let margin = Vector3::new(1.0, 1.0, 1.0) * (max_speed * delta_time + COLLISION_QUERY_MARGIN);
+        (
+            self.get_position() + local_bound_box._min - margin,
+            self.get_position() + local_bound_box._max + margin,
+        )
+    }
+
+    // collision box relative to the character position, taken from the render object bounds
+    pub fn get_local_bound_box(&self) -> BoundingBox {
+        let render_object = self._render_object.borrow();
+        let bound_box = render_object.get_bound_box();
+        let local_min = bound_box._min - self._render_position;
+        let local_max = bound_box._max - self._render_position;
+        let is_valid = local_min.iter().chain(local_max.iter()).all(|value| value.is_finite()) && 0.0 < (local_max - local_min).min();
+        if is_valid {
+            BoundingBox::create_bounding_box(&local_min, &local_max)
+        } else {
+            BoundingBox::create_bounding_box(&Vector3::zeros(), &Vector3::zeros())
+        }
+    }
+
+    pub fn get_render_position(&self) -> Vector3<f32> {
+        self._controller._prev_position.lerp(&self._controller._position, self._render_interpolation)
+    }
+
+    pub fn update_transform(&mut self, interpolation: f32) {
+        self._render_interpolation = interpolation;
+        self._render_position = self.get_render_position();
+        self._render_object.borrow_mut().set_transform(
+            &self._render_position,
+            &self._controller._rotation,
+            &self._controller._scale
+        );
     }
 
     pub fn update_animation_blend_masks(&self) {
         let render_object = ptr_as_mut(self._render_object.as_ptr());
         if self.is_action(ActionAnimationState::ATTACK) {
-            let additive_animation_play_info = render_object.get_animation_play_info(AnimationLayer::AdditiveLayer);
-            if false == additive_animation_play_info._is_animation_end {
-                if self.is_move_state(MoveAnimationState::IDLE) {
-                    render_object.clear_animation_blend_masks(AnimationLayer::AdditiveLayer);
+            if false == render_object.is_animation_end(AnimationLayer::AdditiveLayer) {
+                if ANIMATION_IDLE == self._base_animation_state.get_state() || self._additive_blend_mask.is_empty() {
+                    render_object.clear_blend_masks(AnimationLayer::AdditiveLayer);
                 } else {
-                    render_object.set_animation_blend_masks(
-                        &ptr_as_ref(self._animation_blend_masks)._upper_animation_mask,
-                        AnimationLayer::AdditiveLayer
-                    );
+                    render_object.set_blend_masks(&self._additive_blend_mask, AnimationLayer::AdditiveLayer);
                 }
             }
         }
     }
 
-    pub fn update_character(&mut self, blocks: &Vec<*const RenderObjectData>, delta_time: f32) {
-        if false == self._is_player {
-            self._behavior.update_behavior(ptr_as_mut(self), delta_time);
+    pub fn update_animation_state_machine(&mut self, delta_time: f32) {
+        let animation_graph_data = ptr_as_ref(self._animation_graph_data.as_ptr());
+        let parameters = AnimationParameters {
+            _velocity: self._controller._velocity.clone_owned(),
+            _is_ground: self._controller._is_ground,
+            _is_dead: self.is_dead(),
+            _action: self._action_animation_state,
+            _is_action_started: self._is_action_started,
+            _is_hit_stun: self.is_hit_stun(),
+            _is_hit_started: self._is_hit_started,
+            _move_threshold: animation_graph_data._move_threshold,
+        };
+        self._is_action_started = false;
+        self._is_hit_started = false;
+
+        let render_object = self._render_object.borrow();
+        let base_layer = AnimationLayer::BaseLayer;
+        let additive_layer = AnimationLayer::AdditiveLayer;
+        let base_transition = self._base_animation_state.update_animation_layer_state_machine(
+            &animation_graph_data._base_layer,
+            &parameters,
+            render_object.is_animation_end(base_layer),
+            render_object.get_prev_animation_play_time(base_layer),
+            render_object.get_animation_play_time(base_layer),
+            delta_time,
+            &mut self._animation_events
+        );
+        let additive_transition = self._additive_animation_state.update_animation_layer_state_machine(
+            &animation_graph_data._additive_layer,
+            &parameters,
+            render_object.is_animation_end(additive_layer),
+            render_object.get_prev_animation_play_time(additive_layer),
+            render_object.get_animation_play_time(additive_layer),
+            delta_time,
+            &mut self._animation_events
+        );
+        drop(render_object);
+
+        if let Some(transition) = base_transition {
+            self.play_animation_state(&animation_graph_data._base_layer, &transition._next_state, transition._blend_time, AnimationLayer::BaseLayer);
         }
 
-        self._controller.update_character_controller(&self._render_object.borrow()._bound_box,  blocks, delta_time);
-        self.update_transform();
+        if let Some(transition) = additive_transition {
+            self.play_animation_state(&animation_graph_data._additive_layer, &transition._next_state, transition._blend_time, AnimationLayer::AdditiveLayer);
+        }
 
-        let animation_play_infos = &ptr_as_ref(self._render_object.as_ptr())._animation_play_infos;
+        if base_transition.is_some() || additive_transition.is_some() {
+            self.update_animation_blend_masks();
+        }
+        self.update_animation_events();
+    }
 
-        if false == self.is_move_state(MoveAnimationState::IDLE) && self._controller.is_stop() {
-            self.set_move_idle();
+    // gameplay events, audio and effects are handled by the CharacterManager
+    pub fn update_animation_events(&mut self) {
+        for animation_event in self._animation_events.iter() {
+            if ANIMATION_EVENT_HIT_WINDOW_START == animation_event {
+                if false == self.is_dead() {
+                    self._is_hit_window = true;
+                }
+            } else if ANIMATION_EVENT_HIT_WINDOW_END == animation_event {
+                self._is_hit_window = false;
+            }
         }
+    }
+
+    pub fn update_character(&mut self, blocks: &Vec<*const dyn RenderObjectProxy>, player_position: Option<&Vector3<f32>>, is_behavior_paused: bool, delta_time: f32) {
+        self._animation_events.clear();
+        let character_data = ptr_as_ref(self._character_data.as_ptr());
+        if self.is_dead() {
+            let local_bound_box = self.get_local_bound_box();
+            self._controller.update_character_controller(character_data, &mut self._abilities, &local_bound_box, blocks, delta_time);
+            self.update_animation_state_machine(delta_time);
+            self._dead_time += delta_time;
+            return;
+        }
+
+        self._invulnerable_time = 0f32.max(self._invulnerable_time - delta_time);
+        if self.is_hit_stun() {
+            self._hit_stun_time = 0f32.max(self._hit_stun_time - delta_time);
+            if false == self.is_hit_stun() {
+                self._controller._is_knockback = false;
+            }
+        } else if false == self._is_player && false == is_behavior_paused {
+            self._behavior.update_behavior(ptr_as_mut(self), player_position, delta_time);
+        }
+
+        let local_bound_box = self.get_local_bound_box();
+        self._controller.update_character_controller(character_data, &mut self._abilities, &local_bound_box, blocks, delta_time);
+
+        if self.is_action(ActionAnimationState::ATTACK) {
+            self._prev_action_time = self._action_time;
+            self._action_time += delta_time;
+
+            if self._is_combo_buffered {
+                let cancel_time = self.get_combo_step_data().map_or(0.0, |combo_step_data| combo_step_data._cancel_time);
+                if cancel_time <= self._action_time {
+                    self.start_combo_step(self._combo_step + 1);
+                }
+            }
+        }
+
+        self.update_animation_state_machine(delta_time);
 
         if self.is_action(ActionAnimationState::ATTACK) {
-            if animation_play_infos[AnimationLayer::AdditiveLayer as usize]._is_animation_end {
+            if self._render_object.borrow().is_animation_end(AnimationLayer::AdditiveLayer) {
                 self.set_action_idle();
             }
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use rust_engine_3d::scene::render_object::RenderObjectCreateInfo;
+    use crate::game_module::character::ability::AbilityData;
+    use crate::game_module::headless_scene_backend::HeadlessRenderObject;
+    use super::*;
+
+    const DELTA_TIME: f32 = 1.0 / 60.0;
+
+    fn create_block(min: Vector3<f32>, max: Vector3<f32>) -> Box<HeadlessRenderObject> {
+        let render_object_create_info = RenderObjectCreateInfo {
+            _scale: Vector3::new(1.0, 1.0, 1.0),
+            ..Default::default()
+        };
+        Box::new(HeadlessRenderObject::create_headless_render_object("block", &min, &max, &render_object_create_info))
+    }
+
+    fn get_block_ptrs(blocks: &[Box<HeadlessRenderObject>]) -> Vec<*const dyn RenderObjectProxy> {
+        blocks.iter().map(|block| block.as_ref() as &dyn RenderObjectProxy as *const dyn RenderObjectProxy).collect()
+    }
+
+    fn get_local_bound_box() -> BoundingBox {
+        BoundingBox::create_bounding_box(&Vector3::new(-0.25, 0.0, -0.25), &Vector3::new(0.25, 1.0, 0.25))
+    }
+
+    fn create_controller(position: Vector3<f32>, is_ground: bool) -> CharacterController {
+        let mut controller = CharacterController::create_character_controller();
+        controller._position = position;
+        controller._is_ground = is_ground;
+        controller
+    }
+
+    fn update_controller(
+        controller: &mut CharacterController,
+        character_data: &CharacterData,
+        abilities: &mut CharacterAbilities,
+        blocks: &[Box<HeadlessRenderObject>],
+        tick_count: usize
+    ) {
+        let block_ptrs = get_block_ptrs(blocks);
+        for _ in 0..tick_count {
+            controller.update_character_controller(character_data, abilities, &get_local_bound_box(), &block_ptrs, DELTA_TIME);
+        }
+    }
+
+    #[test]
+    fn test_fast_fall_onto_thin_platform() {
+        let blocks = vec![create_block(Vector3::new(-5.0, 19.9, -1.0), Vector3::new(5.0, 20.0, 1.0))];
+        let mut character_data = CharacterData::default();
+        character_data._max_fall_speed = 1000.0;
+        let mut abilities = CharacterAbilities::create_character_abilities(&character_data._abilities);
+        let mut controller = create_controller(Vector3::new(0.0, 30.0, 0.0), false);
+        // 15 units per tick, more than the platform is thick
+        controller._velocity.y = -900.0;
+        update_controller(&mut controller, &character_data, &mut abilities, &blocks, 10);
+        assert!((controller._position.y - 20.0).abs() < 1e-4);
+        assert!(controller._is_ground);
+    }
+
+    #[test]
+    fn test_ceiling_hit_stops_rising() {
+        let blocks = vec![create_block(Vector3::new(-5.0, 12.0, -1.0), Vector3::new(5.0, 12.1, 1.0))];
+        let character_data = CharacterData::default();
+        let mut abilities = CharacterAbilities::create_character_abilities(&character_data._abilities);
+        let mut controller = create_controller(Vector3::new(0.0, 10.5, 0.0), true);
+        controller.set_move_jump();
+        update_controller(&mut controller, &character_data, &mut abilities, &blocks, 4);
+        assert!(controller._position.y + get_local_bound_box()._max.y <= 12.0 + 1e-4);
+        assert!(controller._velocity.y <= 0.0);
+    }
+
+    #[test]
+    fn test_fast_move_into_wall() {
+        let blocks = vec![
+            create_block(Vector3::new(1.0, 9.0, -1.0), Vector3::new(1.05, 15.0, 1.0)),
+            create_block(Vector3::new(-5.0, 8.0, -1.0), Vector3::new(5.0, 9.0, 1.0)),
+        ];
+        let mut character_data = CharacterData::default();
+        // about 8 units per tick, more than the wall is thick
+        character_data._move_speed = 500.0;
+        let mut abilities = CharacterAbilities::create_character_abilities(&character_data._abilities);
+        let mut controller = create_controller(Vector3::new(0.0, 9.0, 0.0), true);
+        for _ in 0..5 {
+            controller._move_direction = Vector3::new(1.0, 0.0, 0.0);
+            update_controller(&mut controller, &character_data, &mut abilities, &blocks, 1);
+        }
+        assert!((controller._position.x - 0.75).abs() < 1e-4);
+        assert!(controller._is_ground);
+        assert!(controller.is_on_wall());
+    }
+
+    fn tick_controller(
+        controller: &mut CharacterController,
+        character_data: &CharacterData,
+        abilities: &mut CharacterAbilities,
+        blocks: &[Box<HeadlessRenderObject>],
+        is_jump: bool,
+        move_x: f32
+    ) {
+        if is_jump {
+            controller.set_move_jump();
+        }
+        controller._move_direction = Vector3::new(move_x, 0.0, 0.0);
+        update_controller(controller, character_data, abilities, blocks, 1);
+        controller.reset_input();
+    }
+
+    fn create_double_jump_data() -> CharacterData {
+        let mut character_data = CharacterData::default();
+        character_data._abilities.insert(AbilityType::DoubleJump, AbilityData {
+            _speed: 10.0,
+            ..Default::default()
+        });
+        character_data
+    }
+
+    fn walk_off_ledge(character_data: &CharacterData, blocks: &[Box<HeadlessRenderObject>]) -> CharacterController {
+        let mut abilities = CharacterAbilities::create_character_abilities(&character_data._abilities);
+        let mut controller = create_controller(Vector3::new(-0.5, 9.0, 0.0), true);
+        for _ in 0..100 {
+            tick_controller(&mut controller, character_data, &mut abilities, blocks, false, 1.0);
+            if false == controller._is_ground {
+                break;
+            }
+        }
+        assert!(false == controller._is_ground);
+        controller
+    }
+
+    #[test]
+    fn test_coyote_jump() {
+        let blocks = vec![create_block(Vector3::new(-5.0, 8.0, -1.0), Vector3::new(0.0, 9.0, 1.0))];
+        let character_data = CharacterData::default();
+        let mut abilities = CharacterAbilities::create_character_abilities(&character_data._abilities);
+
+        // inside the window
+        let mut controller = walk_off_ledge(&character_data, &blocks);
+        tick_controller(&mut controller, &character_data, &mut abilities, &blocks, false, 0.0);
+        tick_controller(&mut controller, &character_data, &mut abilities, &blocks, true, 0.0);
+        assert!(0.0 < controller._velocity.y);
+
+        // outside the window
+        let mut controller = walk_off_ledge(&character_data, &blocks);
+        let tick_count = (character_data._coyote_time / DELTA_TIME).ceil() as usize;
+        for _ in 0..tick_count {
+            tick_controller(&mut controller, &character_data, &mut abilities, &blocks, false, 0.0);
+        }
+        tick_controller(&mut controller, &character_data, &mut abilities, &blocks, true, 0.0);
+        assert!(controller._velocity.y < 0.0);
+    }
+
+    #[test]
+    fn test_buffered_jump_on_landing() {
+        let blocks = vec![create_block(Vector3::new(-5.0, 8.0, -1.0), Vector3::new(5.0, 9.0, 1.0))];
+        let character_data = create_double_jump_data();
+        let mut abilities = CharacterAbilities::create_character_abilities(&character_data._abilities);
+        let mut controller = create_controller(Vector3::new(0.0, 9.3, 0.0), false);
+        controller._velocity.y = -3.0;
+
+        // the press is kept for the landing instead of spending the double jump
+        tick_controller(&mut controller, &character_data, &mut abilities, &blocks, true, 0.0);
+        assert!(controller._velocity.y < 0.0);
+        assert!(abilities.can_use_ability(AbilityType::DoubleJump));
+
+        let mut is_jumped = false;
+        for _ in 0..6 {
+            tick_controller(&mut controller, &character_data, &mut abilities, &blocks, true, 0.0);
+            if 0.0 < controller._velocity.y {
+                is_jumped = true;
+                break;
+            }
+        }
+        assert!(is_jumped);
+        assert!(character_data._jump_speed - GRAVITY * DELTA_TIME <= controller._velocity.y);
+    }
+
+    #[test]
+    fn test_double_jump_far_from_ground() {
+        let blocks = vec![create_block(Vector3::new(-5.0, 8.0, -1.0), Vector3::new(5.0, 9.0, 1.0))];
+        let character_data = create_double_jump_data();
+        let mut abilities = CharacterAbilities::create_character_abilities(&character_data._abilities);
+        let mut controller = create_controller(Vector3::new(0.0, 20.0, 0.0), false);
+        controller._velocity.y = -3.0;
+        tick_controller(&mut controller, &character_data, &mut abilities, &blocks, true, 0.0);
+        assert!(0.0 < controller._velocity.y);
+        assert!(false == abilities.can_use_ability(AbilityType::DoubleJump));
+    }
+
+    #[test]
+    fn test_jump_buffer_counts_down_from_the_next_tick() {
+        let character_data = CharacterData::default();
+        let mut abilities = CharacterAbilities::create_character_abilities(&character_data._abilities);
+        let mut controller = create_controller(Vector3::new(0.0, 20.0, 0.0), false);
+        controller.set_move_jump();
+        controller.update_jump(&character_data, &mut abilities, false, DELTA_TIME);
+        assert_eq!(character_data._jump_buffer_time, controller._jump_buffer_time);
+        controller.update_jump(&character_data, &mut abilities, false, DELTA_TIME);
+        assert!((character_data._jump_buffer_time - DELTA_TIME - controller._jump_buffer_time).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_jump_pressed_too_early() {
+        let blocks = vec![create_block(Vector3::new(-5.0, 8.0, -1.0), Vector3::new(5.0, 9.0, 1.0))];
+        let character_data = CharacterData::default();
+        let mut abilities = CharacterAbilities::create_character_abilities(&character_data._abilities);
+        let mut controller = create_controller(Vector3::new(0.0, 12.0, 0.0), false);
+        for _ in 0..60 {
+            tick_controller(&mut controller, &character_data, &mut abilities, &blocks, true, 0.0);
+        }
+        assert!(controller._is_ground);
+        assert_eq!(0.0, controller._velocity.y);
+    }
+
+    fn get_jump_apex(jump_hold_ticks: usize) -> f32 {
+        let blocks = vec![create_block(Vector3::new(-5.0, 8.0, -1.0), Vector3::new(5.0, 9.0, 1.0))];
+        let character_data = CharacterData::default();
+        let mut abilities = CharacterAbilities::create_character_abilities(&character_data._abilities);
+        let mut controller = create_controller(Vector3::new(0.0, 9.0, 0.0), true);
+        let mut apex = 0f32;
+        for i in 0..120 {
+            tick_controller(&mut controller, &character_data, &mut abilities, &blocks, i < jump_hold_ticks, 0.0);
+            apex = apex.max(controller._position.y);
+        }
+        apex
+    }
+
+    #[test]
+    fn test_short_and_full_jump_apex() {
+        let full_apex = get_jump_apex(120);
+        let short_apex = get_jump_apex(5);
+        assert!(short_apex < full_apex - 0.5);
+        // a full jump reaches v^2 / 2g
+        let character_data = CharacterData::default();
+        let expected_apex = 9.0 + character_data._jump_speed * character_data._jump_speed / (2.0 * GRAVITY);
+        assert!((full_apex - expected_apex).abs() < 0.3);
+    }
+
+    #[test]
+    fn test_wall_slide_and_wall_jump() {
+        let blocks = vec![
+            create_block(Vector3::new(-50.0, 8.0, -1.0), Vector3::new(50.0, 9.0, 1.0)),
+            create_block(Vector3::new(1.0, 9.0, -1.0), Vector3::new(2.0, 40.0, 1.0)),
+        ];
+        let mut character_data = CharacterData::default();
+        character_data._abilities.insert(AbilityType::WallJump, AbilityData {
+            _speed: 10.0,
+            _push_speed: 6.0,
+            _slide_speed: 2.0,
+            ..Default::default()
+        });
+        let mut abilities = CharacterAbilities::create_character_abilities(&character_data._abilities);
+        let mut controller = create_controller(Vector3::new(0.5, 20.0, 0.0), false);
+        for _ in 0..60 {
+            tick_controller(&mut controller, &character_data, &mut abilities, &blocks, false, 1.0);
+        }
+        assert!(false == controller._is_ground);
+        assert!(controller.is_on_wall());
+        assert!(-2.0 - 1e-4 <= controller._velocity.y);
+
+        tick_controller(&mut controller, &character_data, &mut abilities, &blocks, true, 1.0);
+        assert!(9.0 < controller._velocity.y);
+        assert!(controller._velocity.x < -5.0);
+
+        // a locked wall jump doesn't slow the fall
+        abilities.set_unlocked(AbilityType::WallJump, false);
+        let mut controller = create_controller(Vector3::new(0.5, 35.0, 0.0), false);
+        for _ in 0..60 {
+            tick_controller(&mut controller, &character_data, &mut abilities, &blocks, false, 1.0);
+        }
+        assert!(controller._velocity.y < -2.0);
+    }
+
+    fn create_dash_data() -> CharacterData {
+        let mut character_data = CharacterData::default();
+        character_data._abilities.insert(AbilityType::Dash, AbilityData {
+            _speed: 20.0,
+            _duration: 0.2,
+            ..Default::default()
+        });
+        character_data
+    }
+
+    #[test]
+    fn test_ground_dash_stays_on_ground() {
+        let blocks = vec![create_block(Vector3::new(-50.0, 8.0, -1.0), Vector3::new(50.0, 9.0, 1.0))];
+        let character_data = create_dash_data();
+        let mut abilities = CharacterAbilities::create_character_abilities(&character_data._abilities);
+        let mut controller = create_controller(Vector3::new(0.0, 9.0, 0.0), true);
+        controller.set_move_dash();
+        for _ in 0..6 {
+            tick_controller(&mut controller, &character_data, &mut abilities, &blocks, false, 1.0);
+            assert!(controller.is_dashing());
+            assert!(controller._is_ground);
+        }
+        assert!(1.0 < controller._position.x);
+    }
+
+    #[test]
+    fn test_ground_dash_off_ledge() {
+        let blocks = vec![create_block(Vector3::new(-50.0, 8.0, -1.0), Vector3::new(0.0, 9.0, 1.0))];
+        let character_data = create_dash_data();
+        let mut abilities = CharacterAbilities::create_character_abilities(&character_data._abilities);
+        let mut controller = create_controller(Vector3::new(-0.5, 9.0, 0.0), true);
+        controller.set_move_dash();
+        tick_controller(&mut controller, &character_data, &mut abilities, &blocks, false, 1.0);
+        update_controller(&mut controller, &character_data, &mut abilities, &blocks, 5);
+        assert!(controller.is_dashing());
+        assert!(false == controller._is_ground);
+    }
+}
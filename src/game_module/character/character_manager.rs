@@ -1,54 +1,54 @@
 use std::collections::HashMap;
-use rust_engine_3d::audio::audio_manager::AudioLoop;
 
-use rust_engine_3d::core::engine_core::EngineCore;
+use nalgebra::Vector3;
 use rust_engine_3d::effect::effect_data::EffectCreateInfo;
-use rust_engine_3d::scene::render_object::{RenderObjectCreateInfo, RenderObjectData};
+use rust_engine_3d::scene::render_object::RenderObjectCreateInfo;
 use rust_engine_3d::utilities::system::{newRcRefCell, ptr_as_mut, ptr_as_ref, RcRefCell};
 
-use crate::application::application::Application;
-use crate::game_module::character::animation_blend_mask::AnimationBlendMasks;
-use crate::game_module::character::character::{Character, CharacterCreateInfo};
-use crate::game_module::game_client::GameClient;
+use crate::game_module::character::ability::AbilityType;
+use crate::game_module::character::animation_state_machine::AnimationGraphData;
+use crate::game_module::character::attack::AttackData;
+use crate::game_module::character::character::*;
+use crate::game_module::game_constants::CHARACTER_SPATIAL_HASH_CELL_SIZE;
 use crate::game_module::game_resource::GameResources;
-use crate::game_module::game_scene_manager::GameSceneManager;
+use crate::game_module::game_scene_manager::{GameSceneManager, OutOfBoundsAction};
+use crate::game_module::scene_backend::AnimationHandle;
+use crate::game_module::spatial_hash::SpatialHash;
 
 pub type CharacterMap = HashMap<u64, RcRefCell<Character>>;
 
 pub struct CharacterManager {
-    pub _game_client: *const GameClient,
     pub _game_scene_manager: *const GameSceneManager,
     pub _game_resources: *const GameResources,
-    pub _animation_blend_masks: Box<AnimationBlendMasks>,
     pub _id_generator: u64,
     pub _player: Option<RcRefCell<Character>>,
-    pub _characters: CharacterMap
+    pub _characters: CharacterMap,
+    pub _character_spatial_hash: SpatialHash<u64>,
+    // npcs don't act and attacks don't hit while paused, e.g. during a cutscene
+    pub _is_combat_paused: bool,
 }
 
 impl CharacterManager {
     pub fn create_character_manager() -> Box<CharacterManager> {
         Box::new(CharacterManager {
-            _game_client: std::ptr::null(),
             _game_scene_manager: std::ptr::null(),
             _game_resources: std::ptr::null(),
-            _animation_blend_masks: Box::new(AnimationBlendMasks::create_animation_blend_maks()),
             _id_generator: 0,
             _player: None,
             _characters: HashMap::new(),
+            _character_spatial_hash: SpatialHash::create_spatial_hash(CHARACTER_SPATIAL_HASH_CELL_SIZE),
+            _is_combat_paused: false,
         })
     }
 
-    pub fn initialize_character_manager(&mut self, application: &Application) {
+    pub fn initialize_character_manager(&mut self, game_scene_manager: *const GameSceneManager, game_resources: *const GameResources) {
         log::info!("initialize_character_manager");
-        self._game_client = application.get_game_client();
-        self._game_scene_manager = application.get_game_scene_manager();
-        self._game_resources = application.get_game_resources();
+        self._game_scene_manager = game_scene_manager;
+        self._game_resources = game_resources;
     }
     pub fn destroy_character_manager(&mut self) {
 
     }
-    pub fn get_game_client(&self) -> &GameClient { ptr_as_ref(self._game_client) }
-    pub fn get_game_client_mut(&self) -> &mut GameClient { ptr_as_mut(self._game_client) }
     pub fn get_game_scene_manager(&self) -> &GameSceneManager { ptr_as_ref(self._game_scene_manager) }
     pub fn get_game_scene_manager_mut(&self) -> &mut GameSceneManager { ptr_as_mut(self._game_scene_manager) }
     pub fn generate_id(&mut self) -> u64 {
@@ -66,14 +66,32 @@ impl CharacterManager {
             _model_data_name: character_data.borrow()._model_data_name.clone(),
             ..Default::default()
         };
-        let render_object_data = self.get_game_scene_manager().get_scene_manager_mut().add_skeletal_render_object(
+        let scene_backend = self.get_game_scene_manager().get_scene_backend_mut();
+        let render_object_data = scene_backend.add_skeletal_render_object(
             character_name,
             &render_object_create_info
         );
-        let idle_animation = game_resources.get_engine_resources().get_mesh_data(&character_data.borrow()._idle_animation_mesh);
-        let walk_animation = game_resources.get_engine_resources().get_mesh_data(&character_data.borrow()._walk_animation_mesh);
-        let jump_animation = game_resources.get_engine_resources().get_mesh_data(&character_data.borrow()._jump_animation_mesh);
-        let attack_animation = game_resources.get_engine_resources().get_mesh_data(&character_data.borrow()._attack_animation_mesh);
+        let mut animations: HashMap<String, AnimationHandle> = HashMap::new();
+        for (animation_name, animation_mesh) in character_data.borrow()._animation_set.iter() {
+            animations.insert(animation_name.clone(), scene_backend.get_animation(animation_mesh));
+        }
+        let animation_graph_data_name = character_data.borrow()._animation_graph_data_name.clone();
+        let animation_graph_data = if game_resources.has_animation_graph_data(&animation_graph_data_name) {
+            game_resources.get_animation_graph_data(&animation_graph_data_name).clone()
+        } else {
+            log::warn!("animation graph {} not found, using the default graph", animation_graph_data_name);
+            newRcRefCell(AnimationGraphData::default())
+        };
+        let additive_blend_mask_name = character_data.borrow()._additive_blend_mask_name.clone();
+        let additive_blend_mask: HashMap<String, f32> = if additive_blend_mask_name.is_empty() {
+            HashMap::new()
+        } else if game_resources.has_animation_blend_mask_data(&additive_blend_mask_name) {
+            let animation_blend_mask_data = game_resources.get_animation_blend_mask_data(&additive_blend_mask_name).borrow();
+            animation_blend_mask_data.create_animation_blend_mask(&*render_object_data.borrow())
+        } else {
+            log::warn!("animation blend mask {} not found", additive_blend_mask_name);
+            HashMap::new()
+        };
         let id = self.generate_id();
         let character = newRcRefCell(Character::create_character_instance(
             id,
@@ -81,65 +99,393 @@ impl CharacterManager {
             character_name,
             character_data,
             &render_object_data,
-            idle_animation,
-            walk_animation,
-            jump_animation,
-            attack_animation,
-            self._animation_blend_masks.as_ref(),
+            animations,
+            &animation_graph_data,
+            additive_blend_mask,
             &character_create_info._position,
             &character_create_info._rotation,
             &character_create_info._scale
         ));
+        character.borrow_mut()._controller.set_movement_mode(self.get_game_scene_manager().get_movement_mode());
         if is_player {
             self._player = Some(character.clone());
         }
         self._characters.insert(id, character.clone());
+        let (bound_box_min, bound_box_max) = character.borrow().get_collision_bound_box();
+        self._character_spatial_hash.insert(id, &bound_box_min, &bound_box_max);
         character
     }
     pub fn remove_character(&mut self, character: &RcRefCell<Character>) {
-        self._characters.remove(&character.borrow().get_character_id());
-        self.get_game_scene_manager().get_scene_manager_mut().remove_skeletal_render_object(&character.borrow()._character_name);
+        let character_id = character.borrow().get_character_id();
+        self._characters.remove(&character_id);
+        self._character_spatial_hash.remove(character_id);
+        self.get_game_scene_manager().get_scene_backend_mut().remove_skeletal_render_object(&character.borrow()._character_name);
+    }
+    pub fn get_character_by_name(&self, character_name: &str) -> Option<&RcRefCell<Character>> {
+        self._characters.values().find(|character| character.borrow()._character_name == character_name)
+    }
+    pub fn has_player(&self) -> bool {
+        self._player.is_some()
     }
     pub fn get_player(&self) -> &RcRefCell<Character> {
         self._player.as_ref().unwrap()
     }
-    pub fn update_character_manager(&mut self, _engine_core: &EngineCore, delta_time: f64) {
-        let rock00 = self.get_game_scene_manager().get_scene_manager().get_static_render_object("rock00");
-        let rock01 = self.get_game_scene_manager().get_scene_manager().get_static_render_object("rock01");
-        let blocks: Vec<*const RenderObjectData> = vec![rock00.unwrap().as_ptr(), rock01.unwrap().as_ptr()];
+    pub fn unlock_player_ability(&self, ability_type: AbilityType) {
+        if false == self.has_player() {
+            log::warn!("unlock_player_ability: there is no player");
+        } else if self.get_player().borrow_mut().unlock_ability(ability_type) {
+            log::info!("unlock_player_ability: {:?}", ability_type);
+        } else {
+            log::warn!("the player has no {:?} ability to unlock", ability_type);
+        }
+    }
+    pub fn query_characters_in_region(&self, min: &Vector3<f32>, max: &Vector3<f32>) -> Vec<RcRefCell<Character>> {
+        self._character_spatial_hash.query_region(min, max).iter().filter_map(|character_id| self.get_character(*character_id).cloned()).collect()
+    }
+    // characters hit by the ray, nearest first
+    pub fn query_characters_on_ray(&self, origin: &Vector3<f32>, direction: &Vector3<f32>, max_distance: f32) -> Vec<(RcRefCell<Character>, f32)> {
+        self._character_spatial_hash.query_ray(origin, direction, max_distance).iter().filter_map(|(character_id, distance)| {
+            self.get_character(*character_id).map(|character| (character.clone(), *distance))
+        }).collect()
+    }
+    pub fn is_combat_paused(&self) -> bool {
+        self._is_combat_paused
+    }
+    pub fn set_combat_paused(&mut self, is_combat_paused: bool) {
+        self._is_combat_paused = is_combat_paused;
+    }
+    pub fn update_character_manager(&mut self, delta_time: f64) {
+        let game_scene_manager = self.get_game_scene_manager();
+
+        // scenes without a player, e.g. npc only soak tests, just run the npcs
+        let player_position: Option<Vector3<f32>> = match self._player.as_ref() {
+            Some(player) if false == player.borrow().is_dead() => Some(player.borrow().get_position().clone_owned()),
+            _ => None,
+        };
 
         for character in self._characters.values() {
             let mut character_mut = character.borrow_mut();
-            character_mut.update_character(&blocks, delta_time as f32);
+            let (region_min, region_max) = character_mut.get_collision_query_region(delta_time as f32);
+            let colliders = game_scene_manager.query_colliders_in_region(&region_min, &region_max);
+            character_mut.update_character(&colliders, player_position.as_ref(), self._is_combat_paused, delta_time as f32);
+            self.update_animation_events(&character_mut);
         }
 
-        let mut dead_characters: Vec<RcRefCell<Character>> = Vec::new();
-        let player = ptr_as_ref(self._player.as_ref().unwrap().as_ptr());
-        if player.is_attacking() {
-            self.get_game_client().get_application().get_audio_manager_mut().create_audio_instance_from_bank("swoosh", AudioLoop::ONCE);
-            for character in self._characters.values() {
-                let character_ref = character.borrow_mut();
-                if character_ref._character_id != player._character_id {
-                    if character_ref.collide_bound_box(&player.get_attack_point()) {
-                        dead_characters.push(character.clone());
-
-                        let effect_create_info = EffectCreateInfo {
-                            _effect_position: character_ref.get_attack_point().clone_owned(),
-                            _effect_data_name: String::from("effect_smoke"),
-                            ..Default::default()
-                        };
-
-                        // fx & audio
-                        self.get_game_scene_manager().get_scene_manager_mut().add_effect("hit_effect", &effect_create_info);
-                        self.get_game_client().get_application().get_audio_manager_mut().create_audio_instance_from_bank("hit", AudioLoop::ONCE);
-                        self.get_game_client().get_application().get_audio_manager_mut().create_audio_instance_from_bank("pain_short", AudioLoop::ONCE);
-                    }
+        self.update_character_separation(delta_time as f32);
+        self.update_out_of_bounds();
+        self.update_ability_pickups();
+        self.update_character_spatial_hash();
+
+        // the player hits npcs and npcs hit the player, each target once per swing
+        for attacker in self._characters.values() {
+            let attacker = ptr_as_mut(attacker.as_ptr());
+            if self._is_combat_paused || false == attacker.is_hit_window() {
+                continue;
+            }
+
+            let (region_min, region_max) = match attacker.get_attack_data() {
+                Some(attack_data) => attack_data.get_attack_region(attacker.get_position(), &attacker._controller.get_forward()),
+                None => continue,
+            };
+            for character in self.query_characters_in_region(&region_min, &region_max).iter() {
+                if character.borrow()._is_player == attacker._is_player {
+                    continue;
+                }
+
+                let mut character_mut = character.borrow_mut();
+                let character_id = character_mut.get_character_id();
+                if character_mut._is_collision_enabled && false == character_mut.is_invulnerable() && false == attacker.is_hit_character(character_id) && attacker.collide_attack(&character_mut) {
+                    attacker.add_hit_character(character_id);
+                    let attack_data = attacker.get_attack_data().unwrap();
+                    self.apply_damage(attacker, &mut character_mut, attack_data);
                 }
             }
         }
 
-        for character in dead_characters.iter_mut() {
+        // the player stays in the scene after dying
+        let mut despawn_characters: Vec<RcRefCell<Character>> = Vec::new();
+        for character in self._characters.values() {
+            let character_ref = character.borrow();
+            if false == character_ref._is_player && character_ref.can_despawn() {
+                despawn_characters.push(character.clone());
+            }
+        }
+
+        for character in despawn_characters.iter_mut() {
             self.remove_character(character);
         }
     }
-}
\ No newline at end of file
+    // pushes overlapping characters apart along the horizontal axis between their centers, the lighter one moves further
+    pub fn update_character_separation(&self, delta_time: f32) {
+        let game_scene_manager = self.get_game_scene_manager();
+        for (character_id, character) in self._characters.iter() {
+            if false == character.borrow()._is_collision_enabled {
+                continue;
+            }
+
+            // the hash holds the bounds of the last tick, so look as far as the character can move
+            let (region_min, region_max) = character.borrow().get_collision_query_region(delta_time);
+            for other_character in self.query_characters_in_region(&region_min, &region_max).iter() {
+                let mut other_character_mut = other_character.borrow_mut();
+                let other_character_id = other_character_mut.get_character_id();
+                if other_character_id <= *character_id || false == other_character_mut._is_collision_enabled {
+                    continue;
+                }
+
+                let mut character_mut = character.borrow_mut();
+                let (min, max) = character_mut.get_collision_bound_box();
+                let (other_min, other_max) = other_character_mut.get_collision_bound_box();
+                let overlap = max.inf(&other_max) - min.sup(&other_min);
+                if overlap.x <= 0.0 || overlap.y <= 0.0 || overlap.z <= 0.0 {
+                    continue;
+                }
+
+                let center_offset = (min + max) - (other_min + other_max);
+                let axis = if center_offset.x.abs() < center_offset.z.abs() { 2 } else { 0 };
+                let offset = center_offset[axis];
+                let direction = if 0.0 != offset { offset.signum() } else { -1.0 };
+                let mass = character_mut._character_data.borrow()._mass.max(f32::EPSILON);
+                let other_mass = other_character_mut._character_data.borrow()._mass.max(f32::EPSILON);
+                let total_mass = mass + other_mass;
+
+                let colliders = game_scene_manager.query_colliders_in_region(&region_min, &region_max);
+                let local_bound_box = character_mut.get_local_bound_box();
+                character_mut._controller.push(&local_bound_box, axis, direction * overlap[axis] * other_mass / total_mass, &colliders);
+
+                let (other_region_min, other_region_max) = other_character_mut.get_collision_query_region(delta_time);
+                let other_colliders = game_scene_manager.query_colliders_in_region(&other_region_min, &other_region_max);
+                let other_local_bound_box = other_character_mut.get_local_bound_box();
+                other_character_mut._controller.push(&other_local_bound_box, axis, -direction * overlap[axis] * mass / total_mass, &other_colliders);
+            }
+        }
+    }
+    pub fn update_out_of_bounds(&self) {
+        let out_of_bounds_data = self.get_game_scene_manager().get_out_of_bounds_data();
+        for character in self._characters.values() {
+            let mut character_mut = character.borrow_mut();
+            if character_mut.is_dead() || false == out_of_bounds_data.is_out_of_bounds(character_mut.get_position()) {
+                continue;
+            }
+
+            match out_of_bounds_data.get_out_of_bounds_action(character_mut._is_player) {
+                OutOfBoundsAction::Kill => {
+                    self.get_game_scene_manager().get_scene_backend_mut().play_audio_bank("dead");
+                    character_mut.set_fall_dead();
+                },
+                OutOfBoundsAction::Respawn => character_mut.respawn(),
+            }
+        }
+    }
+    pub fn update_ability_pickups(&self) {
+        let player_position = match self._player.as_ref() {
+            Some(player) if false == player.borrow().is_dead() => player.borrow().get_position().clone_owned(),
+            _ => return,
+        };
+
+        let game_scene_manager = self.get_game_scene_manager_mut();
+        let collected_pickups: Vec<(String, AbilityType)> = game_scene_manager.get_ability_pickups().iter().filter_map(|(pickup_name, pickup_data)| {
+            if (player_position - pickup_data._position).norm() <= pickup_data._radius {
+                Some((pickup_name.clone(), pickup_data._ability_type))
+            } else {
+                None
+            }
+        }).collect();
+
+        for (pickup_name, ability_type) in collected_pickups.iter() {
+            self.unlock_player_ability(*ability_type);
+            game_scene_manager.remove_ability_pickup(pickup_name);
+        }
+    }
+    pub fn update_animation_events(&self, character: &Character) {
+        let scene_backend = self.get_game_scene_manager().get_scene_backend_mut();
+        for animation_event in character._animation_events.iter() {
+            if let Some(audio_bank_name) = animation_event.strip_prefix(ANIMATION_EVENT_PLAY_SOUND) {
+                scene_backend.play_audio_bank(audio_bank_name);
+            } else if let Some(effect_data_name) = animation_event.strip_prefix(ANIMATION_EVENT_PLAY_EFFECT) {
+                let effect_create_info = EffectCreateInfo {
+                    _effect_position: character.get_position().clone_owned(),
+                    _effect_data_name: String::from(effect_data_name),
+                    ..Default::default()
+                };
+                scene_backend.add_effect(effect_data_name, &effect_create_info);
+            } else if ANIMATION_EVENT_FOOTSTEP == animation_event {
+                scene_backend.play_audio_bank(FOOTSTEP_AUDIO_BANK);
+            }
+        }
+    }
+    pub fn apply_damage(&self, attacker: &Character, victim: &mut Character, attack_data: &AttackData) -> DamageOutcome {
+        let damage_outcome = victim._character_property.apply_damage(attack_data._damage as f32);
+        if DamageOutcome::None != damage_outcome {
+            let effect_create_info = EffectCreateInfo {
+                _effect_position: victim.get_bound_box_center(),
+                _effect_data_name: String::from("effect_smoke"),
+                ..Default::default()
+            };
+
+            // fx & audio
+            let scene_backend = self.get_game_scene_manager().get_scene_backend_mut();
+            scene_backend.add_effect("hit_effect", &effect_create_info);
+            scene_backend.play_audio_bank("hit");
+
+            // hit feedback
+            let game_scene_manager = self.get_game_scene_manager_mut();
+            game_scene_manager.add_hit_stop(attack_data._hit_stop_time as f64);
+            game_scene_manager.add_camera_trauma(attack_data._camera_shake);
+
+            if DamageOutcome::Dead == damage_outcome {
+                scene_backend.play_audio_bank("dead");
+                victim.set_dead();
+            } else {
+                scene_backend.play_audio_bank("pain_short");
+                victim.set_hit(attacker.get_position(), &attacker._controller.get_forward(), &attack_data._knockback);
+            }
+        }
+        damage_outcome
+    }
+    pub fn reset_character_inputs(&mut self) {
+        for character in self._characters.values() {
+            character.borrow_mut()._controller.reset_input();
+        }
+    }
+    // the hash holds the simulated collision boxes so queries don't depend on the render interpolation
+    pub fn update_character_spatial_hash(&mut self) {
+        for (character_id, character) in self._characters.iter() {
+            let (bound_box_min, bound_box_max) = character.borrow().get_collision_bound_box();
+            self._character_spatial_hash.update(*character_id, &bound_box_min, &bound_box_max);
+        }
+    }
+    pub fn update_character_transforms(&mut self, interpolation: f32) {
+        for character in self._characters.values() {
+            character.borrow_mut().update_transform(interpolation);
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use crate::application::headless::HeadlessApplication;
+    use super::*;
+
+    const DELTA_TIME: f64 = 1.0 / 60.0;
+
+    fn create_intro_stage() -> Box<HeadlessApplication> {
+        let mut application = HeadlessApplication::create_headless_application();
+        application.initialize_headless_application();
+        application.open_game_scene("intro_stage");
+        // keeps the npcs out of the way
+        application.get_character_manager_mut().set_combat_paused(true);
+        application
+    }
+
+    fn set_player_in_air(character_manager: &CharacterManager, position: &Vector3<f32>) {
+        let mut player = character_manager.get_player().borrow_mut();
+        player._controller._position.clone_from(position);
+        player._controller._velocity = Vector3::zeros();
+        player._controller._is_ground = false;
+    }
+
+    fn press_jump(character_manager: &mut CharacterManager) -> f32 {
+        character_manager.get_player().borrow_mut().set_move_jump();
+        character_manager.update_character_manager(DELTA_TIME);
+        character_manager.reset_character_inputs();
+        // releases the button for the next press
+        character_manager.update_character_manager(DELTA_TIME);
+        character_manager.get_player().borrow()._controller._velocity.y
+    }
+
+    fn place_character(character_manager: &CharacterManager, character_name: &str, position: &Vector3<f32>) -> RcRefCell<Character> {
+        let character = character_manager.get_character_by_name(character_name).unwrap().clone();
+        let mut character_mut = character.borrow_mut();
+        character_mut._controller._position.clone_from(position);
+        character_mut._controller._velocity = Vector3::zeros();
+        drop(character_mut);
+        character
+    }
+
+    fn get_overlap_x(character: &RcRefCell<Character>, other_character: &RcRefCell<Character>) -> f32 {
+        let (min, max) = character.borrow().get_collision_bound_box();
+        let (other_min, other_max) = other_character.borrow().get_collision_bound_box();
+        max.x.min(other_max.x) - min.x.max(other_min.x)
+    }
+
+    #[test]
+    fn test_unlock_player_ability_then_use() {
+        let mut application = create_intro_stage();
+        let character_manager = application.get_character_manager_mut();
+        character_manager.get_player().borrow_mut()._abilities.set_unlocked(AbilityType::DoubleJump, false);
+
+        set_player_in_air(character_manager, &Vector3::new(0.0, 30.0, 0.0));
+        assert!(press_jump(character_manager) < 0.0);
+
+        character_manager.unlock_player_ability(AbilityType::DoubleJump);
+        assert!(character_manager.get_player().borrow()._abilities.is_unlocked(AbilityType::DoubleJump));
+        set_player_in_air(character_manager, &Vector3::new(0.0, 30.0, 0.0));
+        assert!(0.0 < press_jump(character_manager));
+        application.terminate_headless_application();
+    }
+
+    #[test]
+    fn test_ability_pickup_unlocks_ability() {
+        let mut application = create_intro_stage();
+        let pickup_data = application.get_game_scene_manager().get_ability_pickups()["wall_jump_pickup"].clone();
+        let character_manager = application.get_character_manager_mut();
+        assert!(false == character_manager.get_player().borrow()._abilities.is_unlocked(pickup_data._ability_type));
+
+        // out of reach
+        set_player_in_air(character_manager, &(pickup_data._position + Vector3::new(pickup_data._radius + 1.0, 0.0, 0.0)));
+        character_manager.update_character_manager(DELTA_TIME);
+        assert!(false == character_manager.get_player().borrow()._abilities.is_unlocked(pickup_data._ability_type));
+
+        set_player_in_air(character_manager, &pickup_data._position);
+        character_manager.update_character_manager(DELTA_TIME);
+        assert!(character_manager.get_player().borrow()._abilities.is_unlocked(pickup_data._ability_type));
+        assert!(application.get_game_scene_manager().get_ability_pickups().is_empty());
+        application.terminate_headless_application();
+    }
+
+    #[test]
+    fn test_lighter_character_is_pushed_further() {
+        let mut application = create_intro_stage();
+        let character_manager = application.get_character_manager_mut();
+        // jack is lighter than the mutant, both are placed in the air away from the blocks
+        let player = place_character(character_manager, "player", &Vector3::new(0.0, 20.0, 0.0));
+        let enemy = place_character(character_manager, "enemy00", &Vector3::new(0.5, 20.0, 0.0));
+        assert!(player.borrow()._character_data.borrow()._mass < enemy.borrow()._character_data.borrow()._mass);
+        assert!(0.0 < get_overlap_x(&player, &enemy));
+
+        character_manager.update_character_spatial_hash();
+        character_manager.update_character_separation(DELTA_TIME as f32);
+        let player_delta = player.borrow().get_position().x;
+        let enemy_delta = enemy.borrow().get_position().x - 0.5;
+        assert!(player_delta < 0.0 && 0.0 < enemy_delta);
+        assert!(enemy_delta < -player_delta);
+        assert!(get_overlap_x(&player, &enemy) < 1e-4);
+        application.terminate_headless_application();
+    }
+
+    #[test]
+    fn test_separation_does_not_push_into_blocks() {
+        let mut application = create_intro_stage();
+        application.get_game_scene_manager_mut().update_collider_spatial_hash();
+        let rock_position = Vector3::new(2.0, 10.0, 0.0);
+        let rock_bound_box = {
+            let colliders = application.get_game_scene_manager().query_colliders_in_region(&rock_position, &rock_position);
+            let rock = colliders.iter().map(|collider| ptr_as_ref(*collider).get_bound_box()).find(|bound_box| bound_box._min.y <= rock_position.y && rock_position.y <= bound_box._max.y);
+            rock.unwrap().clone()
+        };
+
+        // the player touches the left side of the rock and the heavier mutant pushes it to the right
+        let character_manager = application.get_character_manager_mut();
+        let player_half_width = character_manager.get_player().borrow().get_local_bound_box()._max.x;
+        let player_position = Vector3::new(rock_bound_box._min.x - player_half_width, rock_position.y - 0.5, 0.0);
+        let player = place_character(character_manager, "player", &player_position);
+        let enemy = place_character(character_manager, "enemy01", &(player_position - Vector3::new(0.5, 0.0, 0.0)));
+        assert!(0.0 < get_overlap_x(&player, &enemy));
+
+        character_manager.update_character_spatial_hash();
+        character_manager.update_character_separation(DELTA_TIME as f32);
+        let (_min, max) = player.borrow().get_collision_bound_box();
+        assert!(max.x <= rock_bound_box._min.x + 1e-4);
+        assert!(enemy.borrow().get_position().x < player_position.x - 0.5);
+        application.terminate_headless_application();
+    }
+}
@@ -3,3 +3,7 @@ pub mod character;
 pub mod character_impl;
 pub mod character_manager;
 pub mod animation_blend_mask;
+pub mod behavior_state_machine;
+pub mod animation_state_machine;
+pub mod attack;
+pub mod ability;
@@ -0,0 +1,84 @@
+use nalgebra::Vector3;
+use rust_engine_3d::utilities::bounding_box::BoundingBox;
+
+pub const COLLISION_EPSILON: f32 = 0.001;
+pub const COLLISION_QUERY_MARGIN: f32 = 0.1;
+pub const GROUND_PROBE_DISTANCE: f32 = 0.05;
+
+// Sweeps the box min..max by delta along a single axis and stops it at the first collider in the way.
+// Colliders the box already overlaps are ignored so it can always move out of them.
+// Returns the allowed movement and whether a collider was hit.
+pub fn sweep_bound_box_axis(min: &Vector3<f32>, max: &Vector3<f32>, axis: usize, delta: f32, colliders: &[&BoundingBox]) -> (f32, bool) {
+    let mut allowed_delta = delta;
+    let mut is_hit = false;
+    if 0.0 == delta {
+        return (allowed_delta, is_hit);
+    }
+
+    for collider in colliders.iter() {
+        let is_overlapped = (0..3).filter(|i| *i != axis).all(|i| {
+            min[i] < collider._max[i] - COLLISION_EPSILON && collider._min[i] + COLLISION_EPSILON < max[i]
+        });
+        if false == is_overlapped {
+            continue;
+        }
+
+        if 0.0 < delta && max[axis] <= collider._min[axis] + COLLISION_EPSILON {
+            let distance = 0f32.max(collider._min[axis] - max[axis]);
+            if distance < allowed_delta {
+                allowed_delta = distance;
+                is_hit = true;
+            }
+        } else if delta < 0.0 && collider._max[axis] - COLLISION_EPSILON <= min[axis] {
+            let distance = 0f32.min(collider._max[axis] - min[axis]);
+            if allowed_delta < distance {
+                allowed_delta = distance;
+                is_hit = true;
+            }
+        }
+    }
+    (allowed_delta, is_hit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_box(min: Vector3<f32>, max: Vector3<f32>) -> BoundingBox {
+        BoundingBox::create_bounding_box(&min, &max)
+    }
+
+    #[test]
+    fn test_sweep_zero_delta() {
+        // resting on the floor, a zero delta never reports a hit
+        let floor = create_box(Vector3::new(-5.0, -1.0, -5.0), Vector3::new(5.0, 0.0, 5.0));
+        let result = sweep_bound_box_axis(&Vector3::new(-0.5, 0.0, -0.5), &Vector3::new(0.5, 1.0, 0.5), 1, 0.0, &[&floor]);
+        assert_eq!((0.0, false), result);
+    }
+
+    #[test]
+    fn test_sweep_fast_fall_onto_thin_platform() {
+        let platform = create_box(Vector3::new(-5.0, 19.9, -5.0), Vector3::new(5.0, 20.0, 5.0));
+        let (delta, is_hit) = sweep_bound_box_axis(&Vector3::new(-0.5, 30.0, -0.5), &Vector3::new(0.5, 31.0, 0.5), 1, -100.0, &[&platform]);
+        assert!(is_hit);
+        assert!((delta + 10.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_sweep_fast_move_into_wall() {
+        let wall = create_box(Vector3::new(2.0, 0.0, -5.0), Vector3::new(2.05, 10.0, 5.0));
+        let (delta, is_hit) = sweep_bound_box_axis(&Vector3::new(-0.5, 0.0, -0.5), &Vector3::new(0.5, 1.0, 0.5), 0, 50.0, &[&wall]);
+        assert!(is_hit);
+        assert!((delta - 1.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_sweep_ignores_colliders_off_the_path() {
+        // beside the path on another axis, and already overlapped
+        let beside = create_box(Vector3::new(2.0, 5.0, -5.0), Vector3::new(3.0, 10.0, 5.0));
+        let overlapped = create_box(Vector3::new(0.0, 0.0, -5.0), Vector3::new(1.0, 1.0, 5.0));
+        let (delta, is_hit) = sweep_bound_box_axis(&Vector3::new(-0.5, 0.0, -0.5), &Vector3::new(0.5, 1.0, 0.5), 0, 5.0, &[&beside, &overlapped]);
+        assert!(false == is_hit);
+        assert_eq!(5.0, delta);
+    }
+}
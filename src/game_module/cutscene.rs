@@ -0,0 +1,321 @@
+use nalgebra::Vector3;
+use rust_engine_3d::utilities::system::RcRefCell;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum CameraInterpolation {
+    Linear,
+    // catmull-rom spline through the keyframes
+    Spline,
+}
+
+// rotation is pitch, yaw, roll in radians
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct CameraKeyframeData {
+    pub _time: f32,
+    pub _position: Vector3<f32>,
+    pub _rotation: Vector3<f32>,
+    pub _fov: f32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+#[serde(default)]
+pub struct AnimationTriggerData {
+    pub _time: f32,
+    // name of the character in the game scene
+    pub _character_name: String,
+    pub _animation_name: String,
+    pub _loop: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+#[serde(default)]
+pub struct SoundTriggerData {
+    pub _time: f32,
+    pub _audio_bank_name: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+#[serde(default)]
+pub struct EffectTriggerData {
+    pub _time: f32,
+    pub _effect_data_name: String,
+    pub _position: Vector3<f32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct CutsceneData {
+    // the cutscene ends at this time, even when tracks have later keys
+    pub _duration: f32,
+    pub _camera_interpolation: CameraInterpolation,
+    // keyframes sorted by time, an empty track leaves the camera alone
+    pub _camera_track: Vec<CameraKeyframeData>,
+    pub _animation_track: Vec<AnimationTriggerData>,
+    pub _sound_track: Vec<SoundTriggerData>,
+    pub _effect_track: Vec<EffectTriggerData>,
+}
+
+pub struct CameraSample {
+    pub _position: Vector3<f32>,
+    pub _rotation: Vector3<f32>,
+    pub _fov: f32,
+}
+
+pub struct CutscenePlayer {
+    pub _cutscene_data_name: String,
+    pub _cutscene_data: Option<RcRefCell<CutsceneData>>,
+    pub _prev_time: f32,
+    pub _time: f32,
+    // the next update ends the cutscene and fires all the remaining events
+    pub _is_skipped: bool,
+}
+
+impl Default for CameraKeyframeData {
+    fn default() -> CameraKeyframeData {
+        CameraKeyframeData {
+            _time: 0.0,
+            _position: Vector3::zeros(),
+            _rotation: Vector3::zeros(),
+            _fov: 60.0,
+        }
+    }
+}
+
+impl Default for CutsceneData {
+    fn default() -> CutsceneData {
+        CutsceneData {
+            _duration: 0.0,
+            _camera_interpolation: CameraInterpolation::Spline,
+            _camera_track: Vec::new(),
+            _animation_track: Vec::new(),
+            _sound_track: Vec::new(),
+            _effect_track: Vec::new(),
+        }
+    }
+}
+
+pub fn catmull_rom(p0: f32, p1: f32, p2: f32, p3: f32, t: f32) -> f32 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    0.5 * ((2.0 * p1) + (p2 - p0) * t + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2 + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3)
+}
+
+pub fn catmull_rom_vector(p0: &Vector3<f32>, p1: &Vector3<f32>, p2: &Vector3<f32>, p3: &Vector3<f32>, t: f32) -> Vector3<f32> {
+    Vector3::new(
+        catmull_rom(p0.x, p1.x, p2.x, p3.x, t),
+        catmull_rom(p0.y, p1.y, p2.y, p3.y, t),
+        catmull_rom(p0.z, p1.z, p2.z, p3.z, t),
+    )
+}
+
+// events fire once when the playback time passes them, events at time 0.0 fire on the first update
+pub fn is_triggered(event_time: f32, prev_time: f32, time: f32) -> bool {
+    prev_time <= event_time && event_time < time
+}
+
+// holds the first and the last keyframe outside of the track
+pub fn sample_camera_track(camera_track: &[CameraKeyframeData], interpolation: CameraInterpolation, time: f32) -> Option<CameraSample> {
+    let last_index = camera_track.len().checked_sub(1)?;
+    let index = camera_track.iter().rposition(|keyframe| keyframe._time <= time).unwrap_or(0);
+    if last_index == index || time <= camera_track[0]._time {
+        let keyframe = &camera_track[index];
+        return Some(CameraSample {
+            _position: keyframe._position.clone_owned(),
+            _rotation: keyframe._rotation.clone_owned(),
+            _fov: keyframe._fov,
+        });
+    }
+
+    let k1 = &camera_track[index];
+    let k2 = &camera_track[index + 1];
+    let duration = k2._time - k1._time;
+    let t = if 0.0 < duration { (time - k1._time) / duration } else { 1.0 };
+    match interpolation {
+        CameraInterpolation::Linear => Some(CameraSample {
+            _position: k1._position.lerp(&k2._position, t),
+            _rotation: k1._rotation.lerp(&k2._rotation, t),
+            _fov: k1._fov + (k2._fov - k1._fov) * t,
+        }),
+        CameraInterpolation::Spline => {
+            let k0 = &camera_track[if 0 < index { index - 1 } else { index }];
+            let k3 = &camera_track[last_index.min(index + 2)];
+            Some(CameraSample {
+                _position: catmull_rom_vector(&k0._position, &k1._position, &k2._position, &k3._position, t),
+                _rotation: catmull_rom_vector(&k0._rotation, &k1._rotation, &k2._rotation, &k3._rotation, t),
+                _fov: catmull_rom(k0._fov, k1._fov, k2._fov, k3._fov, t),
+            })
+        },
+    }
+}
+
+impl CutscenePlayer {
+    pub fn create_cutscene_player() -> CutscenePlayer {
+        CutscenePlayer {
+            _cutscene_data_name: String::new(),
+            _cutscene_data: None,
+            _prev_time: 0.0,
+            _time: 0.0,
+            _is_skipped: false,
+        }
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self._cutscene_data.is_some()
+    }
+
+    pub fn get_cutscene_data(&self) -> Option<&RcRefCell<CutsceneData>> {
+        self._cutscene_data.as_ref()
+    }
+
+    pub fn play_cutscene(&mut self, cutscene_data_name: &str, cutscene_data: &RcRefCell<CutsceneData>) {
+        self._cutscene_data_name = String::from(cutscene_data_name);
+        self._cutscene_data = Some(cutscene_data.clone());
+        self._prev_time = 0.0;
+        self._time = 0.0;
+        self._is_skipped = false;
+    }
+
+    pub fn stop_cutscene(&mut self) {
+        self._cutscene_data_name.clear();
+        self._cutscene_data = None;
+    }
+
+    pub fn skip_cutscene(&mut self) {
+        self._is_skipped = true;
+    }
+
+    // returns false once the cutscene has ended, the last update fires all the remaining events
+    pub fn update_cutscene_player(&mut self, delta_time: f32) -> bool {
+        let duration = match self._cutscene_data.as_ref() {
+            Some(cutscene_data) => cutscene_data.borrow()._duration,
+            None => return false,
+        };
+        self._prev_time = self._time;
+        self._time += delta_time;
+        if self._is_skipped || duration <= self._time {
+            self._time = f32::MAX;
+            return false;
+        }
+        true
+    }
+
+    pub fn is_triggered(&self, event_time: f32) -> bool {
+        is_triggered(event_time, self._prev_time, self._time)
+    }
+
+    pub fn sample_camera(&self) -> Option<CameraSample> {
+        let cutscene_data = self._cutscene_data.as_ref()?.borrow();
+        let time = self._time.min(cutscene_data._duration);
+        sample_camera_track(&cutscene_data._camera_track, cutscene_data._camera_interpolation, time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_engine_3d::utilities::system::newRcRefCell;
+    use super::*;
+
+    const DELTA_TIME: f32 = 0.1;
+
+    fn create_keyframe(time: f32, x: f32, fov: f32) -> CameraKeyframeData {
+        CameraKeyframeData {
+            _time: time,
+            _position: Vector3::new(x, 0.0, 0.0),
+            _rotation: Vector3::new(0.0, x * 0.1, 0.0),
+            _fov: fov,
+        }
+    }
+
+    fn create_camera_track() -> Vec<CameraKeyframeData> {
+        vec![create_keyframe(1.0, 0.0, 40.0), create_keyframe(2.0, 2.0, 50.0), create_keyframe(3.0, 3.0, 60.0), create_keyframe(4.0, 7.0, 70.0)]
+    }
+
+    // the events at event_times that fire in each update until the cutscene ends
+    fn play_events(cutscene_player: &mut CutscenePlayer, event_times: &[f32], delta_time: f32, skip_after: Option<usize>) -> Vec<Vec<f32>> {
+        let mut updates: Vec<Vec<f32>> = Vec::new();
+        loop {
+            if Some(updates.len()) == skip_after {
+                cutscene_player.skip_cutscene();
+            }
+            let is_playing = cutscene_player.update_cutscene_player(delta_time);
+            updates.push(event_times.iter().cloned().filter(|event_time| cutscene_player.is_triggered(*event_time)).collect());
+            if false == is_playing {
+                return updates;
+            }
+        }
+    }
+
+    #[test]
+    fn test_camera_track_holds_the_first_and_last_keyframe() {
+        let camera_track = create_camera_track();
+        for interpolation in [CameraInterpolation::Linear, CameraInterpolation::Spline] {
+            let sample = sample_camera_track(&camera_track, interpolation, 0.0).unwrap();
+            assert_eq!(0.0, sample._position.x);
+            assert_eq!(40.0, sample._fov);
+            let sample = sample_camera_track(&camera_track, interpolation, 10.0).unwrap();
+            assert_eq!(7.0, sample._position.x);
+            assert_eq!(70.0, sample._fov);
+        }
+        assert!(sample_camera_track(&[], CameraInterpolation::Spline, 0.0).is_none());
+        let sample = sample_camera_track(&camera_track[..1], CameraInterpolation::Spline, 5.0).unwrap();
+        assert_eq!(0.0, sample._position.x);
+    }
+
+    #[test]
+    fn test_linear_and_spline_interpolation() {
+        let camera_track = create_camera_track();
+        // both pass through the keyframes
+        for keyframe in camera_track.iter() {
+            for interpolation in [CameraInterpolation::Linear, CameraInterpolation::Spline] {
+                let sample = sample_camera_track(&camera_track, interpolation, keyframe._time).unwrap();
+                assert!((sample._position - keyframe._position).norm() < 1e-5);
+                assert!((sample._rotation - keyframe._rotation).norm() < 1e-5);
+                assert!((sample._fov - keyframe._fov).abs() < 1e-4);
+            }
+        }
+
+        let sample = sample_camera_track(&camera_track, CameraInterpolation::Linear, 2.5).unwrap();
+        assert!((sample._position.x - 2.5).abs() < 1e-5);
+        assert!((sample._fov - 55.0).abs() < 1e-4);
+
+        // the spline bends with the neighbouring keyframes instead of following the straight segment
+        let sample = sample_camera_track(&camera_track, CameraInterpolation::Spline, 2.5).unwrap();
+        assert!((sample._position.x - 2.375).abs() < 1e-5);
+        assert!((sample._position.x - catmull_rom(0.0, 2.0, 3.0, 7.0, 0.5)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_trigger_at_zero_fires_on_the_first_update() {
+        let cutscene_data = newRcRefCell(CutsceneData { _duration: 1.0, ..Default::default() });
+        let mut cutscene_player = CutscenePlayer::create_cutscene_player();
+        cutscene_player.play_cutscene("test", &cutscene_data);
+        let updates = play_events(&mut cutscene_player, &[0.0, 0.5], DELTA_TIME, None);
+        assert_eq!(vec![0.0], updates[0]);
+        // every event fires exactly once
+        assert_eq!(1, updates.iter().flatten().filter(|event_time| 0.0 == **event_time).count());
+        assert_eq!(1, updates.iter().flatten().filter(|event_time| 0.5 == **event_time).count());
+    }
+
+    #[test]
+    fn test_remaining_events_flush_on_end_and_skip() {
+        let cutscene_data = newRcRefCell(CutsceneData { _duration: 1.0, ..Default::default() });
+        let event_times = [0.0, 0.5, 1.0, 5.0];
+        let mut cutscene_player = CutscenePlayer::create_cutscene_player();
+
+        // events at and after the duration fire on the last update
+        cutscene_player.play_cutscene("test", &cutscene_data);
+        let updates = play_events(&mut cutscene_player, &event_times, DELTA_TIME, None);
+        assert_eq!(&vec![1.0, 5.0], updates.last().unwrap());
+        assert_eq!(f32::MAX, cutscene_player._time);
+
+        // a skip ends the cutscene on the next update with everything that didn't fire yet
+        cutscene_player.play_cutscene("test", &cutscene_data);
+        let updates = play_events(&mut cutscene_player, &event_times, DELTA_TIME, Some(2));
+        assert_eq!(3, updates.len());
+        assert_eq!(vec![0.0], updates[0]);
+        assert_eq!(vec![0.5, 1.0, 5.0], updates[2]);
+        assert_eq!(f32::MAX, cutscene_player._time);
+    }
+}
@@ -0,0 +1,129 @@
+use crate::game_module::game_constants::{SIMULATION_MAX_SUBSTEPS, SIMULATION_TICK_RATE};
+
+// Accumulates the variable frame time and hands it out as fixed simulation ticks.
+pub struct FixedTimeStep {
+    pub _tick_rate: f64,
+    pub _tick_delta_time: f64,
+    pub _max_substeps: u32,
+    pub _accumulated_time: f64,
+    pub _tick_count: u64,
+}
+
+impl FixedTimeStep {
+    pub fn create_fixed_time_step() -> FixedTimeStep {
+        FixedTimeStep {
+            _tick_rate: SIMULATION_TICK_RATE,
+            _tick_delta_time: 1.0 / SIMULATION_TICK_RATE,
+            _max_substeps: SIMULATION_MAX_SUBSTEPS,
+            _accumulated_time: 0.0,
+            _tick_count: 0,
+        }
+    }
+
+    // returns false and keeps the current rate when tick_rate isn't a positive number
+    pub fn set_tick_rate(&mut self, tick_rate: f64) -> bool {
+        if false == (tick_rate.is_finite() && 0.0 < tick_rate) {
+            log::warn!("invalid tick rate: {}", tick_rate);
+            return false;
+        }
+        self._tick_rate = tick_rate;
+        self._tick_delta_time = 1.0 / tick_rate;
+        self._accumulated_time = 0.0;
+        true
+    }
+
+    // returns false when max_substeps is 0, that would never simulate
+    pub fn set_max_substeps(&mut self, max_substeps: u32) -> bool {
+        if 0 == max_substeps {
+            log::warn!("invalid max substeps: {}", max_substeps);
+            return false;
+        }
+        self._max_substeps = max_substeps;
+        true
+    }
+
+    pub fn get_tick_delta_time(&self) -> f64 {
+        self._tick_delta_time
+    }
+
+    // returns how many ticks to simulate this frame
+    pub fn update_fixed_time_step(&mut self, delta_time: f64) -> u32 {
+        self._accumulated_time += delta_time;
+        let mut substeps: u32 = 0;
+        while self._tick_delta_time <= self._accumulated_time {
+            if self._max_substeps <= substeps {
+                // drop the backlog instead of spiralling after a long hitch
+                self._accumulated_time %= self._tick_delta_time;
+                break;
+            }
+            self._accumulated_time -= self._tick_delta_time;
+            substeps += 1;
+        }
+        self._tick_count += substeps as u64;
+        substeps
+    }
+
+    // how far the render frame is between the previous and the current tick, 0.0 ~ 1.0
+    pub fn get_interpolation_alpha(&self) -> f32 {
+        (self._accumulated_time / self._tick_delta_time) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_fixed_time_step(tick_rate: f64, max_substeps: u32) -> FixedTimeStep {
+        let mut fixed_time_step = FixedTimeStep::create_fixed_time_step();
+        assert!(fixed_time_step.set_tick_rate(tick_rate));
+        assert!(fixed_time_step.set_max_substeps(max_substeps));
+        fixed_time_step
+    }
+
+    #[test]
+    fn test_accumulator() {
+        let mut fixed_time_step = create_fixed_time_step(10.0, 8);
+        // frames shorter than a tick accumulate until a tick is due
+        assert_eq!(0, fixed_time_step.update_fixed_time_step(0.06));
+        assert_eq!(1, fixed_time_step.update_fixed_time_step(0.06));
+        assert!((fixed_time_step._accumulated_time - 0.02).abs() < 1e-9);
+        assert_eq!(3, fixed_time_step.update_fixed_time_step(0.3));
+        assert_eq!(4, fixed_time_step._tick_count);
+    }
+
+    #[test]
+    fn test_max_substeps_drops_the_backlog() {
+        let mut fixed_time_step = create_fixed_time_step(10.0, 4);
+        // a 1.05 second hitch only simulates 4 ticks and keeps the remainder below one tick
+        assert_eq!(4, fixed_time_step.update_fixed_time_step(1.05));
+        assert!((fixed_time_step._accumulated_time - 0.05).abs() < 1e-9);
+        // the dropped time doesn't come back in the next frame
+        assert_eq!(0, fixed_time_step.update_fixed_time_step(0.04));
+        assert_eq!(4, fixed_time_step._tick_count);
+    }
+
+    #[test]
+    fn test_interpolation_alpha() {
+        let mut fixed_time_step = create_fixed_time_step(10.0, 8);
+        assert_eq!(0.0, fixed_time_step.get_interpolation_alpha());
+        fixed_time_step.update_fixed_time_step(0.025);
+        assert!((fixed_time_step.get_interpolation_alpha() - 0.25).abs() < 1e-5);
+        fixed_time_step.update_fixed_time_step(0.1);
+        assert!((fixed_time_step.get_interpolation_alpha() - 0.25).abs() < 1e-5);
+        fixed_time_step.update_fixed_time_step(0.05);
+        assert!((fixed_time_step.get_interpolation_alpha() - 0.75).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_invalid_settings_are_rejected() {
+        let mut fixed_time_step = create_fixed_time_step(30.0, 4);
+        for tick_rate in [0.0, -60.0, f64::NAN, f64::INFINITY] {
+            assert!(false == fixed_time_step.set_tick_rate(tick_rate));
+        }
+        assert_eq!(30.0, fixed_time_step._tick_rate);
+        assert!((fixed_time_step.get_tick_delta_time() - 1.0 / 30.0).abs() < 1e-12);
+
+        assert!(false == fixed_time_step.set_max_substeps(0));
+        assert_eq!(4, fixed_time_step._max_substeps);
+    }
+}
@@ -1,9 +1,14 @@
-use nalgebra::Vector2;
+use nalgebra::{Vector2, Vector3};
 use rust_engine_3d::core::engine_core::EngineCore;
+use rust_engine_3d::effect::effect_data::EffectCreateInfo;
+use rust_engine_3d::scene::animation::AnimationPlayArgs;
+use rust_engine_3d::scene::render_object::AnimationLayer;
 use rust_engine_3d::utilities::system::{ptr_as_mut, ptr_as_ref};
+use winit::event::VirtualKeyCode;
 
 use crate::application::application::Application;
 use crate::game_module::character::character_manager::CharacterManager;
+use crate::game_module::cutscene::CutscenePlayer;
 use crate::game_module::game_controller::GameController;
 use crate::game_module::game_resource::GameResources;
 use crate::game_module::game_scene_manager::GameSceneManager;
@@ -17,6 +22,10 @@ pub struct GameClient {
     pub _game_resources: *const GameResources,
     pub _game_controller: *const GameController,
     pub _game_ui_manager: *const GameUIManager,
+    pub _cutscene_player: CutscenePlayer,
+    // camera settings the cutscene overrides, restored when it ends
+    pub _cutscene_restore_rotation: Vector3<f32>,
+    pub _cutscene_restore_fov: f32,
 }
 
 impl GameClient {
@@ -29,6 +38,9 @@ impl GameClient {
             _game_resources: std::ptr::null(),
             _game_controller: std::ptr::null(),
             _game_ui_manager: std::ptr::null(),
+            _cutscene_player: CutscenePlayer::create_cutscene_player(),
+            _cutscene_restore_rotation: Vector3::zeros(),
+            _cutscene_restore_fov: 0.0,
         })
     }
 
@@ -72,6 +84,105 @@ impl GameClient {
     pub fn start_game(&mut self) {
         log::info!("start_game");
         self.get_game_scene_manager_mut().open_game_scene_data("intro_stage");
+        self.get_game_controller_mut().reset_camera_rig();
+
+        let intro_cutscene = self.get_game_resources().get_game_scene_data("intro_stage").borrow()._intro_cutscene.clone();
+        if false == intro_cutscene.is_empty() {
+            self.play_cutscene(&intro_cutscene);
+        }
+    }
+
+    pub fn is_cutscene_playing(&self) -> bool {
+        self._cutscene_player.is_playing()
+    }
+
+    pub fn play_cutscene(&mut self, cutscene_data_name: &str) -> bool {
+        let game_resources = ptr_as_ref(self._game_resources);
+        if false == game_resources.has_cutscene_data(cutscene_data_name) {
+            log::warn!("cutscene data {} not found", cutscene_data_name);
+            return false;
+        }
+
+        if self.is_cutscene_playing() {
+            self.stop_cutscene();
+        }
+
+        log::info!("play_cutscene: {:?}", cutscene_data_name);
+        let main_camera = ptr_as_ref(self._game_scene_manager).get_scene_manager().get_main_camera();
+        self._cutscene_restore_rotation = main_camera._transform_object.get_rotation().clone_owned();
+        self._cutscene_restore_fov = main_camera._fov;
+        self._cutscene_player.play_cutscene(cutscene_data_name, game_resources.get_cutscene_data(cutscene_data_name));
+        self.get_character_manager_mut().set_combat_paused(true);
+        self.get_character_manager_mut().reset_character_inputs();
+        true
+    }
+
+    pub fn stop_cutscene(&mut self) {
+        let has_camera_track = match self._cutscene_player.get_cutscene_data() {
+            Some(cutscene_data) => false == cutscene_data.borrow()._camera_track.is_empty(),
+            None => return,
+        };
+
+        if has_camera_track {
+            let main_camera = self.get_game_scene_manager().get_scene_manager().get_main_camera_mut();
+            main_camera._transform_object.set_rotation(&self._cutscene_restore_rotation);
+            main_camera._fov = self._cutscene_restore_fov;
+            main_camera._updated_projection = true;
+            self.get_game_controller_mut().reset_camera_rig();
+        }
+        self._cutscene_player.stop_cutscene();
+        self.get_character_manager_mut().set_combat_paused(false);
+    }
+
+    pub fn update_cutscene(&mut self, delta_time: f64) {
+        let is_playing = self._cutscene_player.update_cutscene_player(delta_time as f32);
+        let cutscene_data = match self._cutscene_player.get_cutscene_data() {
+            Some(cutscene_data) => cutscene_data.clone(),
+            None => return,
+        };
+        let cutscene_data = cutscene_data.borrow();
+
+        // triggers
+        let scene_backend = self.get_game_scene_manager().get_scene_backend_mut();
+        for animation_trigger in cutscene_data._animation_track.iter().filter(|trigger| self._cutscene_player.is_triggered(trigger._time)) {
+            match self.get_character_manager().get_character_by_name(&animation_trigger._character_name) {
+                Some(character) => {
+                    let animation_play_args = AnimationPlayArgs {
+                        _animation_loop: animation_trigger._loop,
+                        ..Default::default()
+                    };
+                    character.borrow().play_animation(&animation_trigger._animation_name, &animation_play_args, AnimationLayer::BaseLayer);
+                },
+                None => log::warn!("cutscene character {} not found", animation_trigger._character_name),
+            }
+        }
+
+        for sound_trigger in cutscene_data._sound_track.iter().filter(|trigger| self._cutscene_player.is_triggered(trigger._time)) {
+            scene_backend.play_audio_bank(&sound_trigger._audio_bank_name);
+        }
+
+        for effect_trigger in cutscene_data._effect_track.iter().filter(|trigger| self._cutscene_player.is_triggered(trigger._time)) {
+            let effect_create_info = EffectCreateInfo {
+                _effect_position: effect_trigger._position.clone_owned(),
+                _effect_data_name: effect_trigger._effect_data_name.clone(),
+                ..Default::default()
+            };
+            scene_backend.add_effect(&effect_trigger._effect_data_name, &effect_create_info);
+        }
+
+        // camera
+        if let Some(camera_sample) = self._cutscene_player.sample_camera() {
+            let main_camera = self.get_game_scene_manager().get_scene_manager().get_main_camera_mut();
+            main_camera._transform_object.set_position(&camera_sample._position);
+            main_camera._transform_object.set_rotation(&camera_sample._rotation);
+            main_camera._fov = camera_sample._fov;
+            main_camera._updated_projection = true;
+        }
+
+        drop(cutscene_data);
+        if false == is_playing {
+            self.stop_cutscene();
+        }
     }
 
     pub fn set_game_mode(&mut self, _is_game_mode: bool) {
@@ -83,6 +194,14 @@ impl GameClient {
     }
 
     pub fn update_game_mode(&mut self, _delta_time: f64) {
+        // player input is paused while a cutscene runs, enter skips it
+        if self.is_cutscene_playing() {
+            if self.get_engine_core()._keyboard_input_data.get_key_pressed(VirtualKeyCode::Return) {
+                self._cutscene_player.skip_cutscene();
+            }
+            return;
+        }
+
         let engine_core = self.get_engine_core();
         let game_scene_manager = self.get_game_scene_manager();
         let scene_manager = game_scene_manager.get_scene_manager();
@@ -95,9 +214,9 @@ impl GameClient {
             mouse_move_data._mouse_pos_delta.x as f32 / mouse_speed_ratio,
             mouse_move_data._mouse_pos_delta.y as f32 / mouse_speed_ratio,
         );
-        let player = self.get_character_manager().get_player();
         let main_camera = scene_manager.get_main_camera_mut();
-        if false == self._game_controller.is_null() {
+        if false == self._game_controller.is_null() && self.get_character_manager().has_player() {
+            let player = self.get_character_manager().get_player();
             let game_controller = ptr_as_mut(self._game_controller);
             game_controller.update_game_controller(
                 time_data,
@@ -3,17 +3,41 @@ pub const EDITOR_CAMERA_MOVE_SPEED: f32 = 20.0;
 pub const EDITOR_CAMERA_PAN_SPEED: f32 = 0.05;
 pub const EDITOR_CAMERA_ROTATION_SPEED: f32 = 0.005;
 
+// simulation
+pub const SIMULATION_TICK_RATE: f64 = 60.0;
+pub const SIMULATION_MAX_SUBSTEPS: u32 = 5;
+// time scale of the simulation while a hit stop is running
+pub const HIT_STOP_TIME_SCALE: f64 = 0.0;
+pub const COLLIDER_SPATIAL_HASH_CELL_SIZE: f32 = 4.0;
+pub const CHARACTER_SPATIAL_HASH_CELL_SIZE: f32 = 2.0;
+
 // game constant
 pub const CAMERA_OFFSET_Y: f32 = 1.5;
 pub const CAMERA_PITCH: f32 = 0.2;
 pub const CAMERA_DISTANCE_MIN: f32 = 4.0;
 pub const CAMERA_DISTANCE_MAX: f32 = 10.0;
 pub const CAMERA_ZOOM_SPEED: f32 = 4.0;
+pub const CAMERA_ROTATION_SPEED: f32 = 0.005;
+pub const CAMERA_DEAD_ZONE_X: f32 = 0.5;
+pub const CAMERA_DEAD_ZONE_Y: f32 = 1.0;
+pub const CAMERA_SMOOTH_TIME: f32 = 0.2;
+pub const CAMERA_LOOK_AHEAD_TIME: f32 = 0.3;
+pub const CAMERA_MAX_LOOK_AHEAD: f32 = 2.0;
+pub const CAMERA_LOOK_AHEAD_SMOOTH_TIME: f32 = 0.5;
+pub const CAMERA_SHAKE_MAX_OFFSET: f32 = 0.3;
+pub const CAMERA_SHAKE_MAX_PITCH: f32 = 0.05;
+pub const CAMERA_SHAKE_FREQUENCY: f32 = 15.0;
+pub const CAMERA_SHAKE_TRAUMA_DECAY: f32 = 1.5;
 
 // player
 pub const PLAYER_MOVE_SPEED: f32 = 3.0;
 pub const PLAYER_JUMP_SPEED: f32 = 12.0;
+pub const PLAYER_GRAVITY_SCALE: f32 = 1.0;
+pub const PLAYER_AIR_CONTROL: f32 = 1.0;
+pub const PLAYER_MAX_FALL_SPEED: f32 = 20.0;
+pub const PLAYER_COYOTE_TIME: f32 = 0.1;
+pub const PLAYER_JUMP_BUFFER_TIME: f32 = 0.1;
+pub const PLAYER_JUMP_CUT_OFF_SCALE: f32 = 0.5;
+pub const PLAYER_MASS: f32 = 1.0;
 pub const GRAVITY: f32 = 30.0;
-pub const GROUND_HEIGHT: f32 = 9.0;
-pub const CONTINUOUS_ATTACK_TIME: f32 = 0.15;
-pub const ATTACK_TIME: f32 = 0.15;
+pub const KNOCKBACK_GROUND_FRICTION: f32 = 10.0;
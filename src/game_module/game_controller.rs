@@ -1,4 +1,4 @@
-use nalgebra::Vector2;
+use nalgebra::{Vector2, Vector3};
 use rust_engine_3d::core::engine_core::TimeData;
 use rust_engine_3d::core::input::{KeyboardInputData, MouseInputData, MouseMoveData};
 use rust_engine_3d::scene::camera::CameraObjectData;
@@ -6,7 +6,8 @@ use rust_engine_3d::utilities::system::{ptr_as_mut, ptr_as_ref, RcRefCell};
 use winit::event::VirtualKeyCode;
 
 use crate::application::application::Application;
-use crate::game_module::character::character::Character;
+use crate::game_module::camera_rig::CameraRig;
+use crate::game_module::character::character::{Character, MovementMode};
 use crate::game_module::game_client::GameClient;
 use crate::game_module::game_constants::*;
 use crate::game_module::game_ui_manager::GameUIManager;
@@ -15,7 +16,8 @@ pub struct GameController {
     pub _game_client: *const GameClient,
     pub _game_ui_manager: *const GameUIManager,
     pub _camera_distance: f32,
-    pub _camera_goal_distance: f32
+    pub _camera_goal_distance: f32,
+    pub _camera_rig: CameraRig,
 }
 
 impl GameController {
@@ -25,6 +27,7 @@ impl GameController {
             _game_ui_manager: std::ptr::null(),
             _camera_goal_distance: CAMERA_DISTANCE_MAX,
             _camera_distance: 0.0,
+            _camera_rig: CameraRig::create_camera_rig(),
         })
     }
 
@@ -57,56 +60,98 @@ impl GameController {
             .get_scene_manager()
             .get_main_camera_mut()
     }
+    pub fn reset_camera_rig(&mut self) {
+        self._camera_rig.reset_camera_rig();
+    }
     pub fn update_game_controller(
         &mut self,
         time_data: &TimeData,
         keyboard_input_data: &KeyboardInputData,
         mouse_move_data: &MouseMoveData,
         mouse_input_data: &MouseInputData,
-        _mouse_delta: &Vector2<f32>,
+        mouse_delta: &Vector2<f32>,
         main_camera: &mut CameraObjectData,
         player: &RcRefCell<Character>
     ) {
         let btn_left: bool = mouse_input_data._btn_l_pressed;
         let _btn_right: bool = mouse_input_data._btn_r_pressed;
-        let _btn_right_hold: bool = mouse_input_data._btn_r_hold;
-
+        let btn_right_hold: bool = mouse_input_data._btn_r_hold;
+        let movement_mode = self.get_game_client().get_game_scene_manager().get_movement_mode();
 
         let is_left = keyboard_input_data.get_key_hold(VirtualKeyCode::Left) | keyboard_input_data.get_key_hold(VirtualKeyCode::A);
         let is_right = keyboard_input_data.get_key_hold(VirtualKeyCode::Right) | keyboard_input_data.get_key_hold(VirtualKeyCode::D);
-        let is_jump = keyboard_input_data.get_key_hold(VirtualKeyCode::Up) | keyboard_input_data.get_key_hold(VirtualKeyCode::W) | keyboard_input_data.get_key_hold(VirtualKeyCode::Space);
+        let is_forward = keyboard_input_data.get_key_hold(VirtualKeyCode::Up) | keyboard_input_data.get_key_hold(VirtualKeyCode::W);
+        let is_back = keyboard_input_data.get_key_hold(VirtualKeyCode::Down) | keyboard_input_data.get_key_hold(VirtualKeyCode::S);
+        let is_jump = match movement_mode {
+            MovementMode::SideScroller => is_forward | keyboard_input_data.get_key_hold(VirtualKeyCode::Space),
+            MovementMode::Free3D => keyboard_input_data.get_key_hold(VirtualKeyCode::Space),
+        };
+        let is_dash = keyboard_input_data.get_key_pressed(VirtualKeyCode::LShift);
         let _modifier_keys_ctrl = keyboard_input_data.get_key_hold(VirtualKeyCode::LControl);
         let mut player_mut = player.borrow_mut();
 
         // update player control
-        if is_left || is_right {
-            player_mut.set_move_walk(is_left);
+        match movement_mode {
+            MovementMode::SideScroller => {
+                if is_left || is_right {
+                    player_mut.set_move_walk(is_left);
+                }
+            },
+            MovementMode::Free3D => {
+                if btn_right_hold {
+                    main_camera._transform_object.rotation_yaw(-mouse_delta.x * CAMERA_ROTATION_SPEED);
+                }
+
+                // camera relative move on the ground plane
+                let camera_front = main_camera._transform_object.get_front();
+                let front = Vector3::new(camera_front.x, 0.0, camera_front.z).normalize();
+                let right = Vector3::new(-front.z, 0.0, front.x);
+                let axis_forward = (is_forward as i32 - is_back as i32) as f32;
+                let axis_right = (is_right as i32 - is_left as i32) as f32;
+                let move_direction = front * axis_forward + right * axis_right;
+                if Vector3::zeros() != move_direction {
+                    player_mut.set_move_direction(&move_direction);
+                }
+            },
         }
 
         if is_jump {
             player_mut.set_move_jump();
         }
 
+        if is_dash {
+            player_mut.set_move_dash();
+        }
+
         if btn_left {
             player_mut.set_action_attack();
         }
 
         // update camera
+        let delta_time = time_data._delta_time as f32;
         self._camera_goal_distance -= mouse_move_data._scroll_delta.y as f32;
         self._camera_goal_distance = CAMERA_DISTANCE_MIN.max(CAMERA_DISTANCE_MAX.min(self._camera_goal_distance));
         if self._camera_goal_distance != self._camera_distance {
             let diff = (self._camera_goal_distance - self._camera_distance) * CAMERA_ZOOM_SPEED;
             let sign = diff.signum();
-            let delta =  diff * time_data._delta_time as f32;
+            let delta =  diff * delta_time;
             self._camera_distance += delta;
             if sign != (self._camera_goal_distance - self._camera_distance).signum() {
                 self._camera_distance = self._camera_goal_distance;
             }
-
-            let mut camera_position = player_mut.get_position() - main_camera._transform_object.get_front() * self._camera_distance;
-            camera_position.y += CAMERA_PITCH;
-            main_camera._transform_object.set_position(&camera_position);
-            main_camera._transform_object.set_pitch(CAMERA_PITCH);
         }
+
+        let game_scene_manager = ptr_as_mut(self._game_client).get_game_scene_manager_mut();
+        self._camera_rig.add_camera_trauma(game_scene_manager.take_camera_trauma());
+        let camera_rig_data = game_scene_manager.get_camera_rig_data();
+        let focus_position = self._camera_rig.update_camera_rig(
+            camera_rig_data,
+            &player_mut.get_render_position(),
+            &player_mut._controller._velocity,
+            delta_time
+        );
+        let camera_position = focus_position - main_camera._transform_object.get_front() * self._camera_distance;
+        main_camera._transform_object.set_pitch(CAMERA_PITCH + self._camera_rig.get_shake_pitch());
+        main_camera._transform_object.set_position(&(camera_position + self._camera_rig.get_shake_offset()));
     }
 }
@@ -6,14 +6,20 @@ use rust_engine_3d::renderer::renderer_context::RendererContext;
 use rust_engine_3d::resource::resource::{EngineResources, get_unique_resource_name, APPLICATION_RESOURCE_PATH, ResourceDataMap};
 use rust_engine_3d::utilities::system::{self, newRcRefCell, ptr_as_mut, ptr_as_ref, RcRefCell};
 use serde_json::{self};
+use crate::game_module::character::animation_blend_mask::AnimationBlendMaskData;
+use crate::game_module::character::animation_state_machine::AnimationGraphData;
 use crate::game_module::character::block::BlockData;
 
 use crate::game_module::character::character::CharacterData;
+use crate::game_module::cutscene::CutsceneData;
 use crate::game_module::game_scene_manager::GameSceneDataCreateInfo;
 
 pub const GAME_SCENE_FILE_PATH: &str = "game_data/game_scenes";
 pub const BLOCK_DATA_FILE_PATH: &str = "game_data/blocks";
 pub const CHARACTER_DATA_FILE_PATH: &str = "game_data/characters";
+pub const ANIMATION_GRAPH_DATA_FILE_PATH: &str = "game_data/animation_graphs";
+pub const ANIMATION_BLEND_MASK_DATA_FILE_PATH: &str = "game_data/animation_blend_masks";
+pub const CUTSCENE_DATA_FILE_PATH: &str = "game_data/cutscenes";
 
 pub const EXT_GAME_DATA: &str = "data";
 pub const EXT_GAME_SCENE: &str = "game_scene";
@@ -23,6 +29,9 @@ pub const DEFAULT_GAME_DATA_NAME: &str = "default";
 pub type GameSceneDataCreateInfoMap = ResourceDataMap<GameSceneDataCreateInfo>;
 pub type BlockDataMap = ResourceDataMap<BlockData>;
 pub type CharacterDataMap = ResourceDataMap<CharacterData>;
+pub type AnimationGraphDataMap = ResourceDataMap<AnimationGraphData>;
+pub type AnimationBlendMaskDataMap = ResourceDataMap<AnimationBlendMaskData>;
+pub type CutsceneDataMap = ResourceDataMap<CutsceneData>;
 
 #[derive(Clone)]
 pub struct GameResources {
@@ -30,6 +39,9 @@ pub struct GameResources {
     _game_scene_data_create_infos_map: GameSceneDataCreateInfoMap,
     _block_data_map: BlockDataMap,
     _character_data_map: CharacterDataMap,
+    _animation_graph_data_map: AnimationGraphDataMap,
+    _animation_blend_mask_data_map: AnimationBlendMaskDataMap,
+    _cutscene_data_map: CutsceneDataMap,
 }
 
 impl GameResources {
@@ -39,6 +51,9 @@ impl GameResources {
             _game_scene_data_create_infos_map: GameSceneDataCreateInfoMap::new(),
             _block_data_map: BlockDataMap::new(),
             _character_data_map: CharacterDataMap::new(),
+            _animation_graph_data_map: AnimationGraphDataMap::new(),
+            _animation_blend_mask_data_map: AnimationBlendMaskDataMap::new(),
+            _cutscene_data_map: CutsceneDataMap::new(),
         })
     }
     pub fn get_engine_resources(&self) -> &EngineResources {
@@ -48,25 +63,46 @@ impl GameResources {
         ptr_as_mut(self._engine_resources)
     }
     pub fn collect_resources(&self, dir: &Path, extensions: &[&str]) -> Vec<PathBuf> {
+        if self._engine_resources.is_null() {
+            let mut resource_dir = PathBuf::from(APPLICATION_RESOURCE_PATH);
+            resource_dir.push(dir);
+            let mut resource_files: Vec<PathBuf> = Vec::new();
+            collect_resources_headless(&resource_dir, extensions, &mut resource_files);
+            return resource_files;
+        }
         self.get_engine_resources().collect_resources(dir, extensions)
     }
+    pub fn get_resource_name<T>(&self, resource_data_map: &ResourceDataMap<T>, dir: &PathBuf, file: &PathBuf) -> String {
+        if self._engine_resources.is_null() {
+            let mut resource_dir = PathBuf::from(APPLICATION_RESOURCE_PATH);
+            resource_dir.push(dir);
+            let mut resource_name = file.strip_prefix(&resource_dir).unwrap().to_path_buf();
+            resource_name.set_extension("");
+            return resource_name.to_str().unwrap().replace("\\", "/");
+        }
+        get_unique_resource_name(resource_data_map, dir, file)
+    }
     pub fn initialize_game_resources(&mut self, engine_resources: &EngineResources) {
         self._engine_resources = engine_resources;
     }
-    pub fn load_game_resources(&mut self, renderer_context: &RendererContext) {
-        self.load_game_scene_data(renderer_context);
+    pub fn load_game_resources(&mut self, _renderer_context: &RendererContext) {
+        self.load_game_scene_data();
+        self.load_game_data();
+    }
+    pub fn load_game_resources_headless(&mut self) {
+        self.load_game_scene_data();
         self.load_game_data();
     }
     pub fn destroy_game_resources(&mut self) {
         self.unload_game_data();
         self.unload_game_scene_data();
     }
-    pub fn load_game_scene_data(&mut self, _renderer_context: &RendererContext) {
+    pub fn load_game_scene_data(&mut self) {
         log::info!("    load_game_scene_data");
         let game_scene_directory = PathBuf::from(GAME_SCENE_FILE_PATH);
         let game_scene_data_files: Vec<PathBuf> = self.collect_resources(&game_scene_directory, &[EXT_GAME_SCENE]);
         for game_scene_data_file in game_scene_data_files {
-            let game_scene_data_name = get_unique_resource_name(&self._game_scene_data_create_infos_map, &game_scene_directory, &game_scene_data_file);
+            let game_scene_data_name = self.get_resource_name(&self._game_scene_data_create_infos_map, &game_scene_directory, &game_scene_data_file);
             let loaded_contents = system::load(&game_scene_data_file);
             let game_scene_data_create_info: GameSceneDataCreateInfo = serde_json::from_reader(loaded_contents).expect("Failed to deserialize.");
             self._game_scene_data_create_infos_map.insert(game_scene_data_name.clone(), newRcRefCell(game_scene_data_create_info));
@@ -102,11 +138,17 @@ impl GameResources {
     fn load_game_data(&mut self) {
         log::info!("    load_game_data");
         self.load_block_data();
+        self.load_animation_graph_data();
+        self.load_animation_blend_mask_data();
         self.load_character_data();
+        self.load_cutscene_data();
     }
 
     fn unload_game_data(&mut self) {
+        self.unload_cutscene_data();
         self.unload_character_data();
+        self.unload_animation_blend_mask_data();
+        self.unload_animation_graph_data();
         self.unload_block_data();
     }
 
@@ -117,7 +159,7 @@ impl GameResources {
         // load_block_data
         let game_data_files: Vec<PathBuf> = self.collect_resources(&game_data_directory, &[EXT_GAME_DATA]);
         for game_data_file in game_data_files {
-            let block_data_name = get_unique_resource_name(&self._block_data_map, &game_data_directory, &game_data_file);
+            let block_data_name = self.get_resource_name(&self._block_data_map, &game_data_directory, &game_data_file);
             let loaded_contents = system::load(&game_data_file);
             let block_data: BlockData = serde_json::from_reader(loaded_contents).expect("Failed to deserialize.");
             self._block_data_map.insert(block_data_name.clone(), newRcRefCell(block_data));
@@ -136,6 +178,58 @@ impl GameResources {
         self._block_data_map.get(resource_name).unwrap()
     }
 
+    // animation graph data
+    fn load_animation_graph_data(&mut self) {
+        let game_data_directory = PathBuf::from(ANIMATION_GRAPH_DATA_FILE_PATH);
+
+        // load_animation_graph_data
+        let game_data_files: Vec<PathBuf> = self.collect_resources(&game_data_directory, &[EXT_GAME_DATA]);
+        for game_data_file in game_data_files {
+            let animation_graph_data_name = self.get_resource_name(&self._animation_graph_data_map, &game_data_directory, &game_data_file);
+            let loaded_contents = system::load(&game_data_file);
+            let animation_graph_data: AnimationGraphData = serde_json::from_reader(loaded_contents).expect("Failed to deserialize.");
+            self._animation_graph_data_map.insert(animation_graph_data_name.clone(), newRcRefCell(animation_graph_data));
+        }
+    }
+
+    fn unload_animation_graph_data(&mut self) {
+        self._animation_graph_data_map.clear();
+    }
+
+    pub fn has_animation_graph_data(&self, resource_name: &str) -> bool {
+        self._animation_graph_data_map.get(resource_name).is_some()
+    }
+
+    pub fn get_animation_graph_data(&self, resource_name: &str) -> &RcRefCell<AnimationGraphData> {
+        self._animation_graph_data_map.get(resource_name).unwrap()
+    }
+
+    // animation blend mask data
+    fn load_animation_blend_mask_data(&mut self) {
+        let game_data_directory = PathBuf::from(ANIMATION_BLEND_MASK_DATA_FILE_PATH);
+
+        // load_animation_blend_mask_data
+        let game_data_files: Vec<PathBuf> = self.collect_resources(&game_data_directory, &[EXT_GAME_DATA]);
+        for game_data_file in game_data_files {
+            let animation_blend_mask_data_name = self.get_resource_name(&self._animation_blend_mask_data_map, &game_data_directory, &game_data_file);
+            let loaded_contents = system::load(&game_data_file);
+            let animation_blend_mask_data: AnimationBlendMaskData = serde_json::from_reader(loaded_contents).expect("Failed to deserialize.");
+            self._animation_blend_mask_data_map.insert(animation_blend_mask_data_name.clone(), newRcRefCell(animation_blend_mask_data));
+        }
+    }
+
+    fn unload_animation_blend_mask_data(&mut self) {
+        self._animation_blend_mask_data_map.clear();
+    }
+
+    pub fn has_animation_blend_mask_data(&self, resource_name: &str) -> bool {
+        self._animation_blend_mask_data_map.get(resource_name).is_some()
+    }
+
+    pub fn get_animation_blend_mask_data(&self, resource_name: &str) -> &RcRefCell<AnimationBlendMaskData> {
+        self._animation_blend_mask_data_map.get(resource_name).unwrap()
+    }
+
     // character data
     fn load_character_data(&mut self) {
         let game_data_directory = PathBuf::from(CHARACTER_DATA_FILE_PATH);
@@ -143,7 +237,7 @@ impl GameResources {
         // load_character_data
         let game_data_files: Vec<PathBuf> = self.collect_resources(&game_data_directory, &[EXT_GAME_DATA]);
         for game_data_file in game_data_files {
-            let character_data_name = get_unique_resource_name(&self._character_data_map, &game_data_directory, &game_data_file);
+            let character_data_name = self.get_resource_name(&self._character_data_map, &game_data_directory, &game_data_file);
             let loaded_contents = system::load(&game_data_file);
             let character_data: CharacterData = serde_json::from_reader(loaded_contents).expect("Failed to deserialize.");
             self._character_data_map.insert(character_data_name.clone(), newRcRefCell(character_data));
@@ -161,4 +255,45 @@ impl GameResources {
     pub fn get_character_data(&self, resource_name: &str) -> &RcRefCell<CharacterData> {
         self._character_data_map.get(resource_name).unwrap()
     }
+
+    // cutscene data
+    fn load_cutscene_data(&mut self) {
+        let game_data_directory = PathBuf::from(CUTSCENE_DATA_FILE_PATH);
+
+        // load_cutscene_data
+        let game_data_files: Vec<PathBuf> = self.collect_resources(&game_data_directory, &[EXT_GAME_DATA]);
+        for game_data_file in game_data_files {
+            let cutscene_data_name = self.get_resource_name(&self._cutscene_data_map, &game_data_directory, &game_data_file);
+            let loaded_contents = system::load(&game_data_file);
+            let cutscene_data: CutsceneData = serde_json::from_reader(loaded_contents).expect("Failed to deserialize.");
+            self._cutscene_data_map.insert(cutscene_data_name.clone(), newRcRefCell(cutscene_data));
+        }
+    }
+
+    fn unload_cutscene_data(&mut self) {
+        self._cutscene_data_map.clear();
+    }
+
+    pub fn has_cutscene_data(&self, resource_name: &str) -> bool {
+        self._cutscene_data_map.get(resource_name).is_some()
+    }
+
+    pub fn get_cutscene_data(&self, resource_name: &str) -> &RcRefCell<CutsceneData> {
+        self._cutscene_data_map.get(resource_name).unwrap()
+    }
+}
+
+fn collect_resources_headless(dir: &Path, extensions: &[&str], resource_files: &mut Vec<PathBuf>) {
+    if let Ok(dir_entries) = dir.read_dir() {
+        for dir_entry in dir_entries.flatten() {
+            let path = dir_entry.path();
+            if path.is_dir() {
+                collect_resources_headless(&path, extensions, resource_files);
+            } else if let Some(extension) = path.extension() {
+                if extensions.contains(&extension.to_str().unwrap()) {
+                    resource_files.push(path);
+                }
+            }
+        }
+    }
 }
@@ -9,13 +9,38 @@ use rust_engine_3d::utilities::system::{newRcRefCell, ptr_as_mut, ptr_as_ref, Rc
 use serde::{Deserialize, Serialize};
 
 use crate::application::application::Application;
+use crate::game_module::camera_rig::CameraRigData;
+use crate::game_module::character::ability::AbilityPickupData;
 use crate::game_module::character::block::{Block, BlockCreateInfo};
-use crate::game_module::character::character::CharacterCreateInfo;
+use crate::game_module::character::character::{CharacterCreateInfo, MovementMode};
 use crate::game_module::character::character_manager::CharacterManager;
+use crate::game_module::fixed_time_step::FixedTimeStep;
+use crate::game_module::game_constants::{COLLIDER_SPATIAL_HASH_CELL_SIZE, HIT_STOP_TIME_SCALE, SIMULATION_MAX_SUBSTEPS, SIMULATION_TICK_RATE};
 use crate::game_module::game_resource::GameResources;
+use crate::game_module::scene_backend::{EngineSceneBackend, RenderObjectProxy, SceneBackend};
+use crate::game_module::spatial_hash::SpatialHash;
 
 type BlockCreateInfoMap = HashMap<String, BlockCreateInfo>;
 type CharacterCreateInfoMap = HashMap<String, CharacterCreateInfo>;
+type AbilityPickupDataMap = HashMap<String, AbilityPickupData>;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum OutOfBoundsAction {
+    Kill,
+    Respawn,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct OutOfBoundsData {
+    // characters falling below this height are out of bounds
+    pub _kill_plane_height: f32,
+    // characters leaving this volume are out of bounds
+    pub _bounds_min: Option<Vector3<f32>>,
+    pub _bounds_max: Option<Vector3<f32>>,
+    pub _player_action: OutOfBoundsAction,
+    pub _npc_action: OutOfBoundsAction,
+}
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[serde(default)]
@@ -25,6 +50,52 @@ pub struct GameSceneDataCreateInfo {
     pub _player: CharacterCreateInfoMap,
     pub _characters: CharacterCreateInfoMap,
     pub _start_point: Vector3<f32>,
+    // static render objects of the scene that characters collide with, in addition to the blocks
+    pub _collision_objects: Vec<String>,
+    pub _out_of_bounds: OutOfBoundsData,
+    pub _movement_mode: MovementMode,
+    pub _camera_rig: CameraRigData,
+    // cutscene the game client plays when the scene starts
+    pub _intro_cutscene: String,
+    pub _ability_pickups: AbilityPickupDataMap,
+    // simulation ticks per second and max ticks per frame, the defaults are SIMULATION_TICK_RATE and SIMULATION_MAX_SUBSTEPS
+    pub _tick_rate: Option<f64>,
+    pub _max_substeps: Option<u32>,
+}
+
+impl Default for OutOfBoundsData {
+    fn default() -> OutOfBoundsData {
+        OutOfBoundsData {
+            _kill_plane_height: -100.0,
+            _bounds_min: None,
+            _bounds_max: None,
+            _player_action: OutOfBoundsAction::Respawn,
+            _npc_action: OutOfBoundsAction::Kill,
+        }
+    }
+}
+
+impl OutOfBoundsData {
+    pub fn is_out_of_bounds(&self, position: &Vector3<f32>) -> bool {
+        if position.y < self._kill_plane_height {
+            return true;
+        }
+        if let Some(bounds_min) = self._bounds_min.as_ref() {
+            if position.x < bounds_min.x || position.y < bounds_min.y || position.z < bounds_min.z {
+                return true;
+            }
+        }
+        if let Some(bounds_max) = self._bounds_max.as_ref() {
+            if bounds_max.x < position.x || bounds_max.y < position.y || bounds_max.z < position.z {
+                return true;
+            }
+        }
+        false
+    }
+
+    pub fn get_out_of_bounds_action(&self, is_player: bool) -> OutOfBoundsAction {
+        if is_player { self._player_action } else { self._npc_action }
+    }
 }
 
 pub struct GameSceneManager {
@@ -32,8 +103,21 @@ pub struct GameSceneManager {
     pub _scene_manager: *const SceneManager,
     pub _game_resources: *const GameResources,
     pub _character_manager: *const CharacterManager,
+    pub _scene_backend: Option<Box<dyn SceneBackend>>,
+    pub _fixed_time_step: FixedTimeStep,
+    pub _time_scale: f64,
+    pub _hit_stop_time: f64,
+    // trauma of this frame's damage events, the game controller hands it to the camera rig
+    pub _camera_trauma: f32,
     pub _game_scene_name: String,
     pub _blocks: HashMap<u64, RcRefCell<Block>>,
+    pub _collision_objects: HashMap<u64, RcRefCell<dyn RenderObjectProxy>>,
+    pub _collider_spatial_hash: SpatialHash<u64>,
+    pub _out_of_bounds: OutOfBoundsData,
+    // pickups the player hasn't collected yet
+    pub _ability_pickups: AbilityPickupDataMap,
+    pub _movement_mode: MovementMode,
+    pub _camera_rig: CameraRigData,
     pub _block_id_generator: u64
 }
 
@@ -44,6 +128,12 @@ impl GameSceneManager {
     pub fn get_scene_manager_mut(&self) -> &mut SceneManager {
         ptr_as_mut(self._scene_manager)
     }
+    pub fn get_scene_backend(&self) -> &dyn SceneBackend {
+        self._scene_backend.as_ref().unwrap().as_ref()
+    }
+    pub fn get_scene_backend_mut(&self) -> &mut dyn SceneBackend {
+        ptr_as_mut(self._scene_backend.as_ref().unwrap().as_ref())
+    }
 
     pub fn create_game_scene_manager() -> Box<GameSceneManager> {
         Box::new(GameSceneManager {
@@ -51,8 +141,19 @@ impl GameSceneManager {
             _scene_manager: std::ptr::null(),
             _game_resources: std::ptr::null(),
             _character_manager: std::ptr::null(),
+            _scene_backend: None,
+            _fixed_time_step: FixedTimeStep::create_fixed_time_step(),
+            _time_scale: 1.0,
+            _hit_stop_time: 0.0,
+            _camera_trauma: 0.0,
             _game_scene_name: String::new(),
             _blocks: HashMap::new(),
+            _collision_objects: HashMap::new(),
+            _collider_spatial_hash: SpatialHash::create_spatial_hash(COLLIDER_SPATIAL_HASH_CELL_SIZE),
+            _out_of_bounds: OutOfBoundsData::default(),
+            _ability_pickups: HashMap::new(),
+            _movement_mode: MovementMode::default(),
+            _camera_rig: CameraRigData::default(),
             _block_id_generator: 0,
         })
     }
@@ -68,6 +169,11 @@ impl GameSceneManager {
         self._effect_manager = engine_core.get_effect_manager();
         self._character_manager = application.get_character_manager();
         self._game_resources = application.get_game_resources();
+        self._scene_backend = Some(Box::new(EngineSceneBackend::create_engine_scene_backend(
+            engine_core.get_scene_manager(),
+            engine_core.get_audio_manager(),
+            engine_core.get_engine_resources(),
+        )));
         engine_core.get_scene_manager_mut().initialize_scene_manager(
             engine_core.get_renderer_context(),
             engine_core.get_effect_manager(),
@@ -76,6 +182,18 @@ impl GameSceneManager {
         )
     }
 
+    pub fn initialize_game_scene_manager_headless(
+        &mut self,
+        character_manager: *const CharacterManager,
+        game_resources: *const GameResources,
+        scene_backend: Box<dyn SceneBackend>,
+    ) {
+        log::info!("initialize_game_scene_manager_headless");
+        self._character_manager = character_manager;
+        self._game_resources = game_resources;
+        self._scene_backend = Some(scene_backend);
+    }
+
     pub fn generate_block_id(&mut self) -> u64 {
         let id = self._block_id_generator;
         self._block_id_generator += 1;
@@ -87,7 +205,9 @@ impl GameSceneManager {
     }
 
     pub fn unregister_block(&mut self, block: &RcRefCell<Block>) {
-        self._blocks.remove(&block.borrow().get_block_id());
+        let block_id = block.borrow().get_block_id();
+        self._blocks.remove(&block_id);
+        self._collider_spatial_hash.remove(block_id);
     }
 
     pub fn create_block(&mut self, block_name: &str, block_create_info: &BlockCreateInfo) {
@@ -97,7 +217,7 @@ impl GameSceneManager {
             _model_data_name: block_data.borrow()._model_data_name.clone(),
             ..Default::default()
         };
-        let render_object_data = self.get_scene_manager_mut().add_static_render_object(
+        let render_object_data = self.get_scene_backend_mut().add_static_render_object(
             block_name,
             &render_object_create_info
         );
@@ -111,7 +231,87 @@ impl GameSceneManager {
             &block_create_info._rotation,
             &block_create_info._scale
         ));
-        self._blocks.insert(block_id, block.clone());
+        self.register_block(&block);
+    }
+
+    pub fn get_out_of_bounds_data(&self) -> &OutOfBoundsData {
+        &self._out_of_bounds
+    }
+
+    pub fn get_ability_pickups(&self) -> &AbilityPickupDataMap {
+        &self._ability_pickups
+    }
+
+    pub fn remove_ability_pickup(&mut self, pickup_name: &str) {
+        self._ability_pickups.remove(pickup_name);
+    }
+
+    pub fn get_movement_mode(&self) -> MovementMode {
+        self._movement_mode
+    }
+
+    pub fn get_camera_rig_data(&self) -> &CameraRigData {
+        &self._camera_rig
+    }
+
+    pub fn get_time_scale(&self) -> f64 {
+        self._time_scale
+    }
+
+    pub fn set_time_scale(&mut self, time_scale: f64) {
+        self._time_scale = 0f64.max(time_scale);
+    }
+
+    pub fn is_hit_stop(&self) -> bool {
+        0.0 < self._hit_stop_time
+    }
+
+    // overlapping hit stops don't add up, the longest one wins
+    pub fn add_hit_stop(&mut self, hit_stop_time: f64) {
+        self._hit_stop_time = self._hit_stop_time.max(hit_stop_time);
+    }
+
+    // clamped like the camera shake, nothing takes the trauma outside of the game mode
+    pub fn add_camera_trauma(&mut self, trauma: f32) {
+        self._camera_trauma = 0f32.max(1f32.min(self._camera_trauma + trauma));
+    }
+
+    pub fn take_camera_trauma(&mut self) -> f32 {
+        std::mem::take(&mut self._camera_trauma)
+    }
+
+    pub fn get_collider(&self, collider_id: u64) -> Option<*const dyn RenderObjectProxy> {
+        if let Some(block) = self._blocks.get(&collider_id) {
+            return Some(block.borrow()._render_object.as_ptr());
+        }
+        self._collision_objects.get(&collider_id).map(|collision_object| collision_object.as_ptr() as *const dyn RenderObjectProxy)
+    }
+
+    // the engine refreshes the render object bounds on its own schedule, so every collider is checked each frame,
+    // update only touches the cells of a collider that moved to other cells
+    pub fn update_collider_spatial_hash(&mut self) {
+        for (block_id, block) in self._blocks.iter() {
+            let block = block.borrow();
+            let render_object = block._render_object.borrow();
+            let bound_box = render_object.get_bound_box();
+            self._collider_spatial_hash.update(*block_id, &bound_box._min, &bound_box._max);
+        }
+        for (collider_id, collision_object) in self._collision_objects.iter() {
+            let collision_object = collision_object.borrow();
+            let bound_box = collision_object.get_bound_box();
+            self._collider_spatial_hash.update(*collider_id, &bound_box._min, &bound_box._max);
+        }
+    }
+
+    pub fn query_colliders_in_region(&self, min: &Vector3<f32>, max: &Vector3<f32>) -> Vec<*const dyn RenderObjectProxy> {
+        self._collider_spatial_hash.query_region(min, max).iter().filter_map(|collider_id| self.get_collider(*collider_id)).collect()
+    }
+
+    // colliders hit by the ray, nearest first
+    pub fn query_colliders_on_ray(&self, origin: &Vector3<f32>, direction: &Vector3<f32>, max_distance: f32) -> Vec<(*const dyn RenderObjectProxy, f32)> {
+        self._collider_spatial_hash.query_ray(origin, direction, max_distance).iter().filter_map(|(collider_id, distance)| {
+            self.get_collider(*collider_id).map(|collider| (collider, *distance))
+        }).collect()
     }
 
     pub fn open_game_scene_data(&mut self, game_scene_data_name: &str) {
@@ -126,14 +326,32 @@ impl GameSceneManager {
         // load scene
         let game_scene_data = game_resources.get_game_scene_data(game_scene_data_name).borrow();
         let scene_data_name = &game_scene_data._scene_data_name;
-        self.get_scene_manager_mut()
+        self.get_scene_backend_mut()
             .open_scene_data(scene_data_name);
 
+        self._out_of_bounds = game_scene_data._out_of_bounds.clone();
+        self._ability_pickups = game_scene_data._ability_pickups.clone();
+        self._movement_mode = game_scene_data._movement_mode;
+        self._camera_rig = game_scene_data._camera_rig.clone();
+        self._fixed_time_step.set_tick_rate(game_scene_data._tick_rate.unwrap_or(SIMULATION_TICK_RATE));
+        self._fixed_time_step.set_max_substeps(game_scene_data._max_substeps.unwrap_or(SIMULATION_MAX_SUBSTEPS));
+
         // create blocks
         for (block_name, block_create_info) in game_scene_data._blocks.iter() {
             self.create_block(block_name, block_create_info);
         }
 
+        // collision objects
+        for object_name in game_scene_data._collision_objects.iter() {
+            match self.get_scene_backend().get_static_render_object(object_name) {
+                Some(render_object) => {
+                    let collider_id = self.generate_block_id();
+                    self._collision_objects.insert(collider_id, render_object);
+                },
+                None => log::warn!("collision object {} not found in {}", object_name, scene_data_name),
+            }
+        }
+
         // create player
         let character_manager = ptr_as_mut(self._character_manager);
         for (character_name, character_create_info) in game_scene_data._player.iter() {
@@ -147,13 +365,102 @@ impl GameSceneManager {
     }
 
     pub fn close_game_scene_data(&mut self) {
-        self.get_scene_manager_mut().close_scene_data();
+        self._blocks.clear();
+        self._collision_objects.clear();
+        self._collider_spatial_hash.clear();
+        self._out_of_bounds = OutOfBoundsData::default();
+        self._ability_pickups.clear();
+        self._camera_rig = CameraRigData::default();
+        self._time_scale = 1.0;
+        self._hit_stop_time = 0.0;
+        self._camera_trauma = 0.0;
+        self.get_scene_backend_mut().close_scene_data();
     }
 
     pub fn destroy_game_scene_manager(&mut self) {
         self.get_scene_manager_mut().destroy_scene_manager();
     }
 
-    pub fn update_game_scene_manager(&mut self, _engine_core: &EngineCore, _delta_time: f64) {
+    pub fn update_game_scene_manager(&mut self, delta_time: f64) {
+        self.get_scene_backend_mut().update_scene(delta_time);
+        self.update_collider_spatial_hash();
+
+        // gameplay runs at a fixed tick rate, rendering interpolates between the last two ticks
+        let character_manager = ptr_as_mut(self._character_manager);
+        let simulation_delta_time = if self.is_hit_stop() {
+            // the hit stop runs on real time so it ends even when it freezes the simulation
+            self._hit_stop_time = 0f64.max(self._hit_stop_time - delta_time);
+            delta_time * HIT_STOP_TIME_SCALE
+        } else {
+            delta_time * self._time_scale
+        };
+        let tick_count = self._fixed_time_step.update_fixed_time_step(simulation_delta_time);
+        let tick_delta_time = self._fixed_time_step.get_tick_delta_time();
+        for _ in 0..tick_count {
+            character_manager.update_character_manager(tick_delta_time);
+        }
+
+        if 0 < tick_count {
+            character_manager.reset_character_inputs();
+        }
+        character_manager.update_character_transforms(self._fixed_time_step.get_interpolation_alpha());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::application::headless::{HeadlessApplication, HEADLESS_DELTA_TIME};
+
+    fn create_intro_stage() -> Box<HeadlessApplication> {
+        let mut application = HeadlessApplication::create_headless_application();
+        application.initialize_headless_application();
+        application.open_game_scene("intro_stage");
+        // attacks would add hit stops of their own
+        application.get_character_manager_mut().set_combat_paused(true);
+        application
+    }
+
+    // simulation ticks run during frame_count frames
+    fn update_frames(application: &mut HeadlessApplication, frame_count: usize) -> u64 {
+        let tick_count = application.get_game_scene_manager()._fixed_time_step._tick_count;
+        for _ in 0..frame_count {
+            application.update_headless_application(HEADLESS_DELTA_TIME);
+        }
+        application.get_game_scene_manager()._fixed_time_step._tick_count - tick_count
+    }
+
+    #[test]
+    fn test_hit_stop_freezes_the_simulation() {
+        let mut application = create_intro_stage();
+        assert!(0 < update_frames(&mut application, 10));
+
+        // 0.1 seconds of real time
+        application.get_game_scene_manager_mut().add_hit_stop(0.1);
+        assert_eq!(0, update_frames(&mut application, 5));
+        assert!(application.get_game_scene_manager().is_hit_stop());
+
+        // ticks run again once it expires
+        update_frames(&mut application, 2);
+        assert!(false == application.get_game_scene_manager().is_hit_stop());
+        assert!(0 < update_frames(&mut application, 10));
+        application.terminate_headless_application();
+    }
+
+    #[test]
+    fn test_longest_hit_stop_wins() {
+        let mut application = create_intro_stage();
+        update_frames(&mut application, 10);
+
+        application.get_game_scene_manager_mut().add_hit_stop(0.2);
+        assert_eq!(0, update_frames(&mut application, 2));
+        // a shorter hit stop doesn't cut the running one short
+        application.get_game_scene_manager_mut().add_hit_stop(0.05);
+        assert_eq!(0, update_frames(&mut application, 9));
+        assert!(application.get_game_scene_manager().is_hit_stop());
+
+        update_frames(&mut application, 2);
+        assert!(false == application.get_game_scene_manager().is_hit_stop());
+        assert!(0 < update_frames(&mut application, 10));
+        application.terminate_headless_application();
     }
 }
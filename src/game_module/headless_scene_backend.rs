@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+
+use nalgebra::Vector3;
+use rust_engine_3d::effect::effect_data::EffectCreateInfo;
+use rust_engine_3d::scene::animation::AnimationPlayArgs;
+use rust_engine_3d::scene::render_object::{AnimationLayer, RenderObjectCreateInfo};
+use rust_engine_3d::utilities::bounding_box::BoundingBox;
+use rust_engine_3d::utilities::system::{newRcRefCell, RcRefCell};
+
+use crate::game_module::scene_backend::{AnimationHandle, RenderObjectProxy, SceneBackend};
+
+// there is no mesh data without a renderer, so every animation lasts this long
+pub const HEADLESS_ANIMATION_DURATION: f32 = 1.0;
+pub const HEADLESS_ANIMATION_LAYER_COUNT: usize = 2;
+
+#[derive(Clone, Debug, Default)]
+pub struct HeadlessAnimationPlayInfo {
+    pub _animation_name: String,
+    pub _animation_play_time: f32,
+    pub _prev_animation_play_time: f32,
+    pub _animation_speed: f32,
+    pub _animation_loop: bool,
+    pub _is_animation_end: bool,
+}
+
+pub struct HeadlessRenderObject {
+    pub _object_name: String,
+    pub _local_bound_box_min: Vector3<f32>,
+    pub _local_bound_box_max: Vector3<f32>,
+    pub _bound_box: BoundingBox,
+    pub _animation_play_infos: Vec<HeadlessAnimationPlayInfo>,
+}
+
+#[derive(Default)]
+pub struct HeadlessSceneStatistics {
+    pub _audio_bank_counts: HashMap<String, u32>,
+    pub _effect_counts: HashMap<String, u32>,
+}
+
+pub struct HeadlessSceneBackend {
+    pub _scene_data_name: String,
+    pub _static_render_objects: HashMap<String, RcRefCell<HeadlessRenderObject>>,
+    pub _skeletal_render_objects: HashMap<String, RcRefCell<HeadlessRenderObject>>,
+    pub _statistics: HeadlessSceneStatistics,
+}
+
+impl HeadlessAnimationPlayInfo {
+    pub fn update_animation_play_info(&mut self, delta_time: f32) {
+        if self._animation_name.is_empty() {
+            return;
+        }
+
+        self._prev_animation_play_time = self._animation_play_time;
+        self._animation_play_time += delta_time * self._animation_speed;
+        if HEADLESS_ANIMATION_DURATION <= self._animation_play_time {
+            if self._animation_loop {
+                self._animation_play_time %= HEADLESS_ANIMATION_DURATION;
+            } else {
+                self._animation_play_time = HEADLESS_ANIMATION_DURATION;
+                self._is_animation_end = true;
+            }
+        }
+    }
+}
+
+impl HeadlessRenderObject {
+    pub fn create_headless_render_object(
+        object_name: &str,
+        local_bound_box_min: &Vector3<f32>,
+        local_bound_box_max: &Vector3<f32>,
+        render_object_create_info: &RenderObjectCreateInfo,
+    ) -> HeadlessRenderObject {
+        let mut render_object = HeadlessRenderObject {
+            _object_name: String::from(object_name),
+            _local_bound_box_min: local_bound_box_min.clone_owned(),
+            _local_bound_box_max: local_bound_box_max.clone_owned(),
+            _bound_box: BoundingBox::default(),
+            _animation_play_infos: vec![HeadlessAnimationPlayInfo::default(); HEADLESS_ANIMATION_LAYER_COUNT],
+        };
+        render_object.set_transform(
+            &render_object_create_info._position,
+            &render_object_create_info._rotation,
+            &render_object_create_info._scale,
+        );
+        render_object
+    }
+
+    pub fn update_render_object(&mut self, delta_time: f32) {
+        for animation_play_info in self._animation_play_infos.iter_mut() {
+            animation_play_info.update_animation_play_info(delta_time);
+        }
+    }
+}
+
+impl RenderObjectProxy for HeadlessRenderObject {
+    fn get_bound_box(&self) -> &BoundingBox {
+        &self._bound_box
+    }
+
+    fn set_transform(&mut self, position: &Vector3<f32>, _rotation: &Vector3<f32>, scale: &Vector3<f32>) {
+        // rotation is ignored, the bounds stay axis aligned
+        let bound_box_min = position + self._local_bound_box_min.component_mul(scale);
+        let bound_box_max = position + self._local_bound_box_max.component_mul(scale);
+        self._bound_box = BoundingBox::create_bounding_box(&bound_box_min, &bound_box_max);
+    }
+
+    fn play_animation(&mut self, animation: &AnimationHandle, animation_play_args: &AnimationPlayArgs, layer: AnimationLayer) {
+        let animation_play_info = &mut self._animation_play_infos[layer as usize];
+        if animation_play_info._animation_name == animation._animation_name && false == animation_play_args._force_animation_setting {
+            return;
+        }
+
+        animation_play_info._animation_name = animation._animation_name.clone();
+        animation_play_info._animation_play_time = 0.0;
+        animation_play_info._prev_animation_play_time = 0.0;
+        animation_play_info._animation_speed = animation_play_args._animation_speed;
+        animation_play_info._animation_loop = animation_play_args._animation_loop;
+        animation_play_info._is_animation_end = false;
+    }
+
+    fn get_animation_play_time(&self, layer: AnimationLayer) -> f32 {
+        self._animation_play_infos[layer as usize]._animation_play_time
+    }
+
+    fn get_prev_animation_play_time(&self, layer: AnimationLayer) -> f32 {
+        self._animation_play_infos[layer as usize]._prev_animation_play_time
+    }
+
+    fn is_animation_end(&self, layer: AnimationLayer) -> bool {
+        self._animation_play_infos[layer as usize]._is_animation_end
+    }
+
+    fn set_blend_masks(&mut self, _blend_masks: &HashMap<String, f32>, _layer: AnimationLayer) {
+    }
+
+    fn clear_blend_masks(&mut self, _layer: AnimationLayer) {
+    }
+
+    fn get_bone_children(&self, _bone_name: &str) -> Vec<String> {
+        // headless objects have no skeleton
+        Vec::new()
+    }
+}
+
+impl HeadlessSceneBackend {
+    pub fn create_headless_scene_backend() -> HeadlessSceneBackend {
+        HeadlessSceneBackend {
+            _scene_data_name: String::new(),
+            _static_render_objects: HashMap::new(),
+            _skeletal_render_objects: HashMap::new(),
+            _statistics: HeadlessSceneStatistics::default(),
+        }
+    }
+}
+
+impl SceneBackend for HeadlessSceneBackend {
+    fn open_scene_data(&mut self, scene_data_name: &str) {
+        self._scene_data_name = String::from(scene_data_name);
+    }
+
+    fn close_scene_data(&mut self) {
+        self._static_render_objects.clear();
+        self._skeletal_render_objects.clear();
+    }
+
+    fn update_scene(&mut self, delta_time: f64) {
+        for render_object in self._skeletal_render_objects.values() {
+            render_object.borrow_mut().update_render_object(delta_time as f32);
+        }
+    }
+
+    fn get_animation(&self, animation_name: &str) -> AnimationHandle {
+        AnimationHandle {
+            _animation_name: String::from(animation_name),
+            _mesh_data: None,
+        }
+    }
+
+    fn add_static_render_object(&mut self, object_name: &str, render_object_create_info: &RenderObjectCreateInfo) -> RcRefCell<dyn RenderObjectProxy> {
+        // unit cube around the pivot
+        let render_object = newRcRefCell(HeadlessRenderObject::create_headless_render_object(
+            object_name,
+            &Vector3::new(-1.0, -1.0, -1.0),
+            &Vector3::new(1.0, 1.0, 1.0),
+            render_object_create_info,
+        ));
+        self._static_render_objects.insert(String::from(object_name), render_object.clone());
+        render_object
+    }
+
+    fn add_skeletal_render_object(&mut self, object_name: &str, render_object_create_info: &RenderObjectCreateInfo) -> RcRefCell<dyn RenderObjectProxy> {
+        // human sized box standing on the pivot
+        let render_object = newRcRefCell(HeadlessRenderObject::create_headless_render_object(
+            object_name,
+            &Vector3::new(-0.5, 0.0, -0.5),
+            &Vector3::new(0.5, 2.0, 0.5),
+            render_object_create_info,
+        ));
+        self._skeletal_render_objects.insert(String::from(object_name), render_object.clone());
+        render_object
+    }
+
+    fn get_static_render_object(&self, object_name: &str) -> Option<RcRefCell<dyn RenderObjectProxy>> {
+        self._static_render_objects.get(object_name).map(|render_object| render_object.clone() as RcRefCell<dyn RenderObjectProxy>)
+    }
+
+    fn remove_static_render_object(&mut self, object_name: &str) {
+        self._static_render_objects.remove(object_name);
+    }
+
+    fn remove_skeletal_render_object(&mut self, object_name: &str) {
+        self._skeletal_render_objects.remove(object_name);
+    }
+
+    fn play_audio_bank(&mut self, audio_bank_name: &str) {
+        *self._statistics._audio_bank_counts.entry(String::from(audio_bank_name)).or_insert(0) += 1;
+    }
+
+    fn add_effect(&mut self, _effect_name: &str, effect_create_info: &EffectCreateInfo) {
+        *self._statistics._effect_counts.entry(effect_create_info._effect_data_name.clone()).or_insert(0) += 1;
+    }
+}
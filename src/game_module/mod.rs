@@ -1,8 +1,15 @@
+pub mod camera_rig;
 pub mod character;
+pub mod collision;
+pub mod cutscene;
+pub mod fixed_time_step;
 pub mod game_client;
 pub mod game_constants;
 pub mod game_controller;
 pub mod game_scene_manager;
+pub mod headless_scene_backend;
+pub mod scene_backend;
+pub mod spatial_hash;
 pub mod widgets;
 pub mod game_ui_manager;
 pub mod game_ui_manager_impl;
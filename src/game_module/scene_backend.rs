@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+
+use nalgebra::Vector3;
+use rust_engine_3d::audio::audio_manager::{AudioLoop, AudioManager};
+use rust_engine_3d::effect::effect_data::EffectCreateInfo;
+use rust_engine_3d::resource::resource::EngineResources;
+use rust_engine_3d::scene::animation::AnimationPlayArgs;
+use rust_engine_3d::scene::mesh::MeshData;
+use rust_engine_3d::scene::render_object::{AnimationLayer, RenderObjectCreateInfo, RenderObjectData};
+use rust_engine_3d::scene::scene_manager::SceneManager;
+use rust_engine_3d::utilities::bounding_box::BoundingBox;
+use rust_engine_3d::utilities::system::{ptr_as_mut, ptr_as_ref, RcRefCell};
+
+// Animation reference resolved once by the backend. The mesh is None when there is no renderer.
+#[derive(Clone)]
+pub struct AnimationHandle {
+    pub _animation_name: String,
+    pub _mesh_data: Option<RcRefCell<MeshData>>,
+}
+
+// What gameplay needs from a render object: transform, bounds and animation playback.
+pub trait RenderObjectProxy {
+    fn get_bound_box(&self) -> &BoundingBox;
+    fn set_transform(&mut self, position: &Vector3<f32>, rotation: &Vector3<f32>, scale: &Vector3<f32>);
+    fn play_animation(&mut self, animation: &AnimationHandle, animation_play_args: &AnimationPlayArgs, layer: AnimationLayer);
+    fn get_animation_play_time(&self, layer: AnimationLayer) -> f32;
+    fn get_prev_animation_play_time(&self, layer: AnimationLayer) -> f32;
+    fn is_animation_end(&self, layer: AnimationLayer) -> bool;
+    fn set_blend_masks(&mut self, blend_masks: &HashMap<String, f32>, layer: AnimationLayer);
+    fn clear_blend_masks(&mut self, layer: AnimationLayer);
+    fn get_bone_children(&self, bone_name: &str) -> Vec<String>;
+}
+
+// What gameplay needs from the scene: render objects, animations, audio and effects.
+pub trait SceneBackend {
+    fn open_scene_data(&mut self, scene_data_name: &str);
+    fn close_scene_data(&mut self);
+    fn update_scene(&mut self, delta_time: f64);
+    fn get_animation(&self, animation_name: &str) -> AnimationHandle;
+    fn add_static_render_object(&mut self, object_name: &str, render_object_create_info: &RenderObjectCreateInfo) -> RcRefCell<dyn RenderObjectProxy>;
+    fn add_skeletal_render_object(&mut self, object_name: &str, render_object_create_info: &RenderObjectCreateInfo) -> RcRefCell<dyn RenderObjectProxy>;
+    fn get_static_render_object(&self, object_name: &str) -> Option<RcRefCell<dyn RenderObjectProxy>>;
+    fn remove_static_render_object(&mut self, object_name: &str);
+    fn remove_skeletal_render_object(&mut self, object_name: &str);
+    fn play_audio_bank(&mut self, audio_bank_name: &str);
+    fn add_effect(&mut self, effect_name: &str, effect_create_info: &EffectCreateInfo);
+}
+
+// RenderObjectData
+impl RenderObjectProxy for RenderObjectData {
+    fn get_bound_box(&self) -> &BoundingBox {
+        &self._bound_box
+    }
+
+    fn set_transform(&mut self, position: &Vector3<f32>, rotation: &Vector3<f32>, scale: &Vector3<f32>) {
+        self._transform_object.set_position(position);
+        self._transform_object.set_rotation(rotation);
+        self._transform_object.set_scale(scale);
+    }
+
+    fn play_animation(&mut self, animation: &AnimationHandle, animation_play_args: &AnimationPlayArgs, layer: AnimationLayer) {
+        if let Some(mesh_data) = animation._mesh_data.as_ref() {
+            self.set_animation(mesh_data, animation_play_args, layer);
+        }
+    }
+
+    fn get_animation_play_time(&self, layer: AnimationLayer) -> f32 {
+        self.get_animation_play_info(layer)._animation_play_time
+    }
+
+    fn get_prev_animation_play_time(&self, layer: AnimationLayer) -> f32 {
+        self.get_animation_play_info(layer)._prev_animation_play_time
+    }
+
+    fn is_animation_end(&self, layer: AnimationLayer) -> bool {
+        self.get_animation_play_info(layer)._is_animation_end
+    }
+
+    fn set_blend_masks(&mut self, blend_masks: &HashMap<String, f32>, layer: AnimationLayer) {
+        self.set_animation_blend_masks(blend_masks, layer);
+    }
+
+    fn clear_blend_masks(&mut self, layer: AnimationLayer) {
+        self.clear_animation_blend_masks(layer);
+    }
+
+    fn get_bone_children(&self, bone_name: &str) -> Vec<String> {
+        let mesh_data = self._mesh_data.borrow();
+        for skeleton_data in mesh_data._skeleton_data_list.iter() {
+            if let Some(bone_index) = skeleton_data._bone_index_map.get(bone_name) {
+                return skeleton_data._bones[*bone_index]._children.iter().map(|child| ptr_as_ref(*child)._name.clone()).collect();
+            }
+        }
+        Vec::new()
+    }
+}
+
+// EngineSceneBackend
+pub struct EngineSceneBackend {
+    pub _scene_manager: *const SceneManager,
+    pub _audio_manager: *const AudioManager,
+    pub _engine_resources: *const EngineResources,
+}
+
+impl EngineSceneBackend {
+    pub fn create_engine_scene_backend(
+        scene_manager: *const SceneManager,
+        audio_manager: *const AudioManager,
+        engine_resources: *const EngineResources,
+    ) -> EngineSceneBackend {
+        EngineSceneBackend {
+            _scene_manager: scene_manager,
+            _audio_manager: audio_manager,
+            _engine_resources: engine_resources,
+        }
+    }
+    pub fn get_scene_manager(&self) -> &SceneManager {
+        ptr_as_ref(self._scene_manager)
+    }
+    pub fn get_scene_manager_mut(&self) -> &mut SceneManager {
+        ptr_as_mut(self._scene_manager)
+    }
+    pub fn get_audio_manager_mut(&self) -> &mut AudioManager {
+        ptr_as_mut(self._audio_manager)
+    }
+    pub fn get_engine_resources(&self) -> &EngineResources {
+        ptr_as_ref(self._engine_resources)
+    }
+}
+
+impl SceneBackend for EngineSceneBackend {
+    fn open_scene_data(&mut self, scene_data_name: &str) {
+        self.get_scene_manager_mut().open_scene_data(scene_data_name);
+    }
+
+    fn close_scene_data(&mut self) {
+        self.get_scene_manager_mut().close_scene_data();
+    }
+
+    fn update_scene(&mut self, _delta_time: f64) {
+        // the engine updates transforms, bounds and animations of its render objects
+    }
+
+    fn get_animation(&self, animation_name: &str) -> AnimationHandle {
+        let mesh_data = if animation_name.is_empty() {
+            None
+        } else {
+            Some(self.get_engine_resources().get_mesh_data(animation_name).clone())
+        };
+        AnimationHandle {
+            _animation_name: String::from(animation_name),
+            _mesh_data: mesh_data,
+        }
+    }
+
+    fn add_static_render_object(&mut self, object_name: &str, render_object_create_info: &RenderObjectCreateInfo) -> RcRefCell<dyn RenderObjectProxy> {
+        self.get_scene_manager_mut().add_static_render_object(object_name, render_object_create_info)
+    }
+
+    fn add_skeletal_render_object(&mut self, object_name: &str, render_object_create_info: &RenderObjectCreateInfo) -> RcRefCell<dyn RenderObjectProxy> {
+        self.get_scene_manager_mut().add_skeletal_render_object(object_name, render_object_create_info)
+    }
+
+    fn get_static_render_object(&self, object_name: &str) -> Option<RcRefCell<dyn RenderObjectProxy>> {
+        self.get_scene_manager().get_static_render_object(object_name).map(|render_object| render_object.clone() as RcRefCell<dyn RenderObjectProxy>)
+    }
+
+    fn remove_static_render_object(&mut self, object_name: &str) {
+        self.get_scene_manager_mut().remove_static_render_object(object_name);
+    }
+
+    fn remove_skeletal_render_object(&mut self, object_name: &str) {
+        self.get_scene_manager_mut().remove_skeletal_render_object(object_name);
+    }
+
+    fn play_audio_bank(&mut self, audio_bank_name: &str) {
+        self.get_audio_manager_mut().create_audio_instance_from_bank(audio_bank_name, AudioLoop::ONCE);
+    }
+
+    fn add_effect(&mut self, effect_name: &str, effect_create_info: &EffectCreateInfo) {
+        self.get_scene_manager_mut().add_effect(effect_name, effect_create_info);
+    }
+}
@@ -0,0 +1,279 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use nalgebra::Vector3;
+
+pub type SpatialHashCell = (i32, i32, i32);
+
+#[derive(Clone, Debug)]
+pub struct SpatialHashItem {
+    pub _min: Vector3<f32>,
+    pub _max: Vector3<f32>,
+    pub _cell_min: SpatialHashCell,
+    pub _cell_max: SpatialHashCell,
+}
+
+// Uniform grid broadphase: every item is stored in each cell its bounding box touches.
+pub struct SpatialHash<T: Copy + Eq + Hash> {
+    pub _cell_size: f32,
+    pub _cells: HashMap<SpatialHashCell, Vec<T>>,
+    pub _items: HashMap<T, SpatialHashItem>,
+}
+
+impl<T: Copy + Eq + Hash> SpatialHash<T> {
+    pub fn create_spatial_hash(cell_size: f32) -> SpatialHash<T> {
+        SpatialHash {
+            _cell_size: cell_size,
+            _cells: HashMap::new(),
+            _items: HashMap::new(),
+        }
+    }
+
+    pub fn get_cell(&self, position: &Vector3<f32>) -> SpatialHashCell {
+        (
+            (position.x / self._cell_size).floor() as i32,
+            (position.y / self._cell_size).floor() as i32,
+            (position.z / self._cell_size).floor() as i32,
+        )
+    }
+
+    pub fn len(&self) -> usize {
+        self._items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self._items.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self._cells.clear();
+        self._items.clear();
+    }
+
+    pub fn insert(&mut self, key: T, min: &Vector3<f32>, max: &Vector3<f32>) {
+        self.remove(key);
+        let cell_min = self.get_cell(min);
+        let cell_max = self.get_cell(max);
+        for x in cell_min.0..=cell_max.0 {
+            for y in cell_min.1..=cell_max.1 {
+                for z in cell_min.2..=cell_max.2 {
+                    self._cells.entry((x, y, z)).or_default().push(key);
+                }
+            }
+        }
+        self._items.insert(key, SpatialHashItem {
+            _min: min.clone_owned(),
+            _max: max.clone_owned(),
+            _cell_min: cell_min,
+            _cell_max: cell_max,
+        });
+    }
+
+    pub fn remove(&mut self, key: T) {
+        if let Some(item) = self._items.remove(&key) {
+            for x in item._cell_min.0..=item._cell_max.0 {
+                for y in item._cell_min.1..=item._cell_max.1 {
+                    for z in item._cell_min.2..=item._cell_max.2 {
+                        if let Some(cell) = self._cells.get_mut(&(x, y, z)) {
+                            cell.retain(|cell_key| *cell_key != key);
+                            if cell.is_empty() {
+                                self._cells.remove(&(x, y, z));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // only touches the cells when the item moved to other cells
+    pub fn update(&mut self, key: T, min: &Vector3<f32>, max: &Vector3<f32>) {
+        let cell_min = self.get_cell(min);
+        let cell_max = self.get_cell(max);
+        if let Some(item) = self._items.get_mut(&key) {
+            if item._cell_min == cell_min && item._cell_max == cell_max {
+                item._min.clone_from(min);
+                item._max.clone_from(max);
+                return;
+            }
+        }
+        self.insert(key, min, max);
+    }
+
+    // items whose bounding box overlaps min..max
+    pub fn query_region(&self, min: &Vector3<f32>, max: &Vector3<f32>) -> Vec<T> {
+        let cell_min = self.get_cell(min);
+        let cell_max = self.get_cell(max);
+        let mut visited: HashSet<T> = HashSet::new();
+        let mut result: Vec<T> = Vec::new();
+        for x in cell_min.0..=cell_max.0 {
+            for y in cell_min.1..=cell_max.1 {
+                for z in cell_min.2..=cell_max.2 {
+                    if let Some(cell) = self._cells.get(&(x, y, z)) {
+                        for key in cell.iter() {
+                            if visited.insert(*key) {
+                                let item = &self._items[key];
+                                if item._min.x <= max.x && min.x <= item._max.x &&
+                                    item._min.y <= max.y && min.y <= item._max.y &&
+                                    item._min.z <= max.z && min.z <= item._max.z {
+                                    result.push(*key);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    // items hit by the ray, sorted by distance along the normalized direction
+    pub fn query_ray(&self, origin: &Vector3<f32>, direction: &Vector3<f32>, max_distance: f32) -> Vec<(T, f32)> {
+        let direction = direction.normalize();
+        let end = origin + direction * max_distance;
+        let mut result: Vec<(T, f32)> = Vec::new();
+        for key in self.query_region(&origin.inf(&end), &origin.sup(&end)) {
+            let item = &self._items[&key];
+            if let Some(distance) = intersect_ray_bound_box(origin, &direction, &item._min, &item._max) {
+                if distance <= max_distance {
+                    result.push((key, distance));
+                }
+            }
+        }
+        result.sort_by(|a, b| a.1.total_cmp(&b.1));
+        result
+    }
+}
+
+// slab test, returns the entry distance or 0.0 when the origin is inside the box
+pub fn intersect_ray_bound_box(origin: &Vector3<f32>, direction: &Vector3<f32>, min: &Vector3<f32>, max: &Vector3<f32>) -> Option<f32> {
+    let mut t_min = 0f32;
+    let mut t_max = f32::MAX;
+    for i in 0..3 {
+        if direction[i].abs() < f32::EPSILON {
+            if origin[i] < min[i] || max[i] < origin[i] {
+                return None;
+            }
+        } else {
+            let inv_direction = 1.0 / direction[i];
+            let t0 = (min[i] - origin[i]) * inv_direction;
+            let t1 = (max[i] - origin[i]) * inv_direction;
+            t_min = t_min.max(t0.min(t1));
+            t_max = t_max.min(t0.max(t1));
+            if t_max < t_min {
+                return None;
+            }
+        }
+    }
+    Some(t_min)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_spatial_hash() -> SpatialHash<u64> {
+        let mut spatial_hash = SpatialHash::create_spatial_hash(2.0);
+        spatial_hash.insert(0, &Vector3::new(0.0, 0.0, 0.0), &Vector3::new(1.0, 1.0, 1.0));
+        spatial_hash.insert(1, &Vector3::new(5.0, 0.0, 0.0), &Vector3::new(6.0, 1.0, 1.0));
+        spatial_hash.insert(2, &Vector3::new(-3.0, -3.0, -3.0), &Vector3::new(3.0, -2.0, 3.0));
+        spatial_hash
+    }
+
+    fn query_region_sorted(spatial_hash: &SpatialHash<u64>, min: Vector3<f32>, max: Vector3<f32>) -> Vec<u64> {
+        let mut keys = spatial_hash.query_region(&min, &max);
+        keys.sort();
+        keys
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut spatial_hash = create_spatial_hash();
+        assert_eq!(3, spatial_hash.len());
+        // the large item is stored in every cell it touches, -2..1 on x and z, -2..-1 on y
+        let cell_count = spatial_hash._cells.values().filter(|cell| cell.contains(&2)).count();
+        assert_eq!(4 * 2 * 4, cell_count);
+
+        // inserting an existing key replaces it
+        spatial_hash.insert(0, &Vector3::new(10.0, 0.0, 0.0), &Vector3::new(11.0, 1.0, 1.0));
+        assert_eq!(3, spatial_hash.len());
+        assert!(query_region_sorted(&spatial_hash, Vector3::zeros(), Vector3::new(1.0, 1.0, 1.0)).is_empty());
+        assert_eq!(vec![0], query_region_sorted(&spatial_hash, Vector3::new(10.0, 0.0, 0.0), Vector3::new(10.5, 0.5, 0.5)));
+    }
+
+    #[test]
+    fn test_update() {
+        let mut spatial_hash = create_spatial_hash();
+        // within the same cells only the bounds change
+        spatial_hash.update(0, &Vector3::new(0.5, 0.0, 0.0), &Vector3::new(1.5, 1.0, 1.0));
+        assert_eq!(vec![0], query_region_sorted(&spatial_hash, Vector3::new(1.2, 0.0, 0.0), Vector3::new(1.4, 1.0, 1.0)));
+        assert!(query_region_sorted(&spatial_hash, Vector3::new(0.1, 0.0, 0.0), Vector3::new(0.2, 1.0, 1.0)).is_empty());
+
+        // moving to other cells leaves no stale cells behind
+        spatial_hash.update(0, &Vector3::new(20.0, 0.0, 0.0), &Vector3::new(21.0, 1.0, 1.0));
+        assert!(query_region_sorted(&spatial_hash, Vector3::zeros(), Vector3::new(1.9, 1.9, 1.9)).is_empty());
+        assert!(false == spatial_hash._cells.contains_key(&(0, 0, 0)));
+        assert_eq!(vec![0], query_region_sorted(&spatial_hash, Vector3::new(20.0, 0.0, 0.0), Vector3::new(20.5, 0.5, 0.5)));
+
+        // updating an unknown key inserts it
+        spatial_hash.update(3, &Vector3::new(30.0, 0.0, 0.0), &Vector3::new(31.0, 1.0, 1.0));
+        assert_eq!(4, spatial_hash.len());
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut spatial_hash = create_spatial_hash();
+        spatial_hash.remove(2);
+        assert_eq!(2, spatial_hash.len());
+        assert!(spatial_hash._cells.values().all(|cell| false == cell.contains(&2)));
+        assert!(query_region_sorted(&spatial_hash, Vector3::new(-3.0, -3.0, -3.0), Vector3::new(3.0, -2.0, 3.0)).is_empty());
+
+        // removing a missing key is ignored, empty cells are dropped
+        spatial_hash.remove(2);
+        spatial_hash.remove(0);
+        spatial_hash.remove(1);
+        assert!(spatial_hash.is_empty());
+        assert!(spatial_hash._cells.is_empty());
+    }
+
+    #[test]
+    fn test_query_region() {
+        let spatial_hash = create_spatial_hash();
+        assert_eq!(vec![0, 1, 2], query_region_sorted(&spatial_hash, Vector3::new(-10.0, -10.0, -10.0), Vector3::new(10.0, 10.0, 10.0)));
+        // touching counts as overlapping
+        assert_eq!(vec![0, 1], query_region_sorted(&spatial_hash, Vector3::new(1.0, 0.0, 0.0), Vector3::new(5.0, 1.0, 1.0)));
+        // in a shared cell but outside the bounds
+        assert!(query_region_sorted(&spatial_hash, Vector3::new(1.2, 1.2, 1.2), Vector3::new(1.8, 1.8, 1.8)).is_empty());
+        assert_eq!(vec![2], query_region_sorted(&spatial_hash, Vector3::new(2.5, -2.5, 2.5), Vector3::new(2.6, -2.4, 2.6)));
+    }
+
+    #[test]
+    fn test_query_ray() {
+        let spatial_hash = create_spatial_hash();
+        // the direction doesn't have to be normalized, hits are nearest first
+        let hits = spatial_hash.query_ray(&Vector3::new(-2.0, 0.5, 0.5), &Vector3::new(2.0, 0.0, 0.0), 10.0);
+        assert_eq!(vec![0, 1], hits.iter().map(|hit| hit.0).collect::<Vec<u64>>());
+        assert!((hits[0].1 - 2.0).abs() < 1e-5);
+        assert!((hits[1].1 - 7.0).abs() < 1e-5);
+
+        // max distance cuts the far item off
+        let hits = spatial_hash.query_ray(&Vector3::new(-2.0, 0.5, 0.5), &Vector3::new(1.0, 0.0, 0.0), 5.0);
+        assert_eq!(vec![0], hits.iter().map(|hit| hit.0).collect::<Vec<u64>>());
+
+        // a ray starting inside an item hits it at 0.0
+        let hits = spatial_hash.query_ray(&Vector3::new(0.0, -2.5, 0.0), &Vector3::new(0.0, -1.0, 0.0), 1.0);
+        assert_eq!(vec![(2, 0.0)], hits);
+
+        // a ray passing beside every item
+        assert!(spatial_hash.query_ray(&Vector3::new(-2.0, 5.0, 0.5), &Vector3::new(1.0, 0.0, 0.0), 20.0).is_empty());
+    }
+
+    #[test]
+    fn test_intersect_ray_bound_box() {
+        let min = Vector3::new(-1.0, -1.0, -1.0);
+        let max = Vector3::new(1.0, 1.0, 1.0);
+        assert_eq!(Some(4.0), intersect_ray_bound_box(&Vector3::new(-5.0, 0.0, 0.0), &Vector3::new(1.0, 0.0, 0.0), &min, &max));
+        assert_eq!(None, intersect_ray_bound_box(&Vector3::new(-5.0, 0.0, 0.0), &Vector3::new(-1.0, 0.0, 0.0), &min, &max));
+        assert_eq!(None, intersect_ray_bound_box(&Vector3::new(-5.0, 2.0, 0.0), &Vector3::new(1.0, 0.0, 0.0), &min, &max));
+    }
+}